@@ -0,0 +1,205 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    cancel::CancelToken,
+    directory::ManufacturerDirectory,
+    error::ApiError,
+    material_filter::MaterialFilter,
+    models::{
+        BillOfMaterials, Building, CategoryStats, Ec3Category, Ec3Material, Epd, Node, Org, Plant,
+        Project, TaggedMaterial,
+    },
+    query::Query,
+    APIResult, BatchResult, Ec3api,
+};
+
+/// Async client for runtimes such as tokio, available with the `async` feature.
+///
+/// Wraps a configured [`Ec3api`] and runs each call on the blocking thread pool of the runtime,
+/// so queries, caching, retries and rate limiting behave exactly like the blocking client while
+/// the sleeps between retries don't stall the runtime. Dropping a returned future cancels its
+/// call at the next request, page or retry, as with a [`CancelToken`].
+///
+/// [`Ec3api::iter_materials`] borrows the client and blocks between pages, so it has no async
+/// counterpart; run it on [`blocking`](AsyncEc3api::blocking) in `spawn_blocking` instead.
+#[derive(Clone, Debug)]
+pub struct AsyncEc3api {
+    api: Ec3api,
+}
+
+impl From<Ec3api> for AsyncEc3api {
+    fn from(api: Ec3api) -> Self {
+        AsyncEc3api { api }
+    }
+}
+
+impl AsyncEc3api {
+    pub fn new(api: Ec3api) -> Self {
+        api.into()
+    }
+
+    /// The wrapped blocking client
+    pub fn blocking(&self) -> &Ec3api {
+        &self.api
+    }
+
+    /// Run `call` with a clone of the client on the blocking thread pool, cancelling it if the
+    /// future is dropped before it completes
+    async fn spawn<T, F>(&self, call: F) -> Result<T, ApiError>
+    where
+        T: Send + 'static,
+        F: FnOnce(Ec3api) -> Result<T, ApiError> + Send + 'static,
+    {
+        let token = CancelToken::for_call(self.api.cancel_token.as_ref());
+        let guard = CancelOnDrop(Some(token.clone()));
+        let api = self.api.with(|req| {
+            req.cancel_token(token);
+        });
+        let joined = tokio::task::spawn_blocking(move || call(api)).await;
+        // Completed: leave running whatever the call started in the background
+        guard.disarm();
+        match joined {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(ApiError::TransportError(e.to_string())),
+        }
+    }
+
+    pub async fn fetch(&self) -> Result<Vec<Ec3Material>, ApiError> {
+        self.spawn(|api| api.fetch()).await
+    }
+
+    pub async fn fetch_all(&self) -> APIResult {
+        self.spawn(|api| api.fetch_all()).await
+    }
+
+    /// Fetch many filters concurrently, see [`Ec3api::fetch_batch`]
+    pub async fn fetch_batch(&self, filters: Vec<MaterialFilter>) -> Vec<BatchResult> {
+        match self.spawn(|api| Ok(api.fetch_batch(filters))).await {
+            Ok(results) => results,
+            Err(e) => panic!("Batch fetch failed to run: {e}"),
+        }
+    }
+
+    pub async fn fetch_by_jurisdiction(&self) -> Result<Vec<TaggedMaterial>, ApiError> {
+        self.spawn(|api| api.fetch_by_jurisdiction()).await
+    }
+
+    pub async fn fetch_raw(&self) -> Result<Value, ApiError> {
+        self.spawn(|api| api.fetch_raw()).await
+    }
+
+    pub async fn fetch_as<T: DeserializeOwned + Send + 'static>(&self) -> Result<T, ApiError> {
+        self.spawn(|api| api.fetch_as()).await
+    }
+
+    /// Run a typed query, see [`Ec3api::execute`]
+    pub async fn execute<Q>(&self, query: Q) -> Result<Q::Output, ApiError>
+    where
+        Q: Query + Send + 'static,
+        Q::Output: Send + 'static,
+    {
+        self.spawn(move |api| api.execute(&query)).await
+    }
+
+    pub async fn material_by_id(&self, id: &str) -> Result<Ec3Material, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.material_by_id(&id)).await
+    }
+
+    pub async fn category_by_id(&self, id: &str) -> Result<Node<Ec3Category>, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.category_by_id(&id)).await
+    }
+
+    pub async fn category_stats(&self, category_id: &str) -> Result<CategoryStats, ApiError> {
+        let id = category_id.to_string();
+        self.spawn(move |api| api.category_stats(&id)).await
+    }
+
+    pub async fn epd(&self, id: &str) -> Result<Epd, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.epd(&id)).await
+    }
+
+    /// The EPD of a material, see [`Ec3api::epd_of`]
+    pub async fn epd_of(&self, material: &Ec3Material) -> Result<Option<Epd>, ApiError> {
+        let material = material.clone();
+        self.spawn(move |api| api.epd_of(&material)).await
+    }
+
+    pub async fn plant(&self, id: &str) -> Result<Plant, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.plant(&id)).await
+    }
+
+    /// The plant of a material, see [`Ec3api::plant_of`]
+    pub async fn plant_of(&self, material: &Ec3Material) -> Result<Option<Plant>, ApiError> {
+        let material = material.clone();
+        self.spawn(move |api| api.plant_of(&material)).await
+    }
+
+    pub async fn org(&self, id: &str) -> Result<Org, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.org(&id)).await
+    }
+
+    pub async fn orgs_by_name(&self, name: &str) -> Result<Vec<Org>, ApiError> {
+        let name = name.to_string();
+        self.spawn(move |api| api.orgs_by_name(&name)).await
+    }
+
+    pub async fn manufacturer_directory(&self) -> Result<ManufacturerDirectory, ApiError> {
+        self.spawn(|api| api.manufacturer_directory()).await
+    }
+
+    pub async fn projects(&self) -> Result<Vec<Project>, ApiError> {
+        self.spawn(|api| api.projects()).await
+    }
+
+    pub async fn project(&self, id: &str) -> Result<Project, ApiError> {
+        let id = id.to_string();
+        self.spawn(move |api| api.project(&id)).await
+    }
+
+    pub async fn buildings(&self, project_id: &str) -> Result<Vec<Building>, ApiError> {
+        let id = project_id.to_string();
+        self.spawn(move |api| api.buildings(&id)).await
+    }
+
+    pub async fn bill_of_materials(
+        &self,
+        building: &Building,
+    ) -> Result<BillOfMaterials, ApiError> {
+        let building = building.clone();
+        self.spawn(move |api| api.bill_of_materials(&building))
+            .await
+    }
+
+    pub async fn project_bills_of_materials(
+        &self,
+        project_id: &str,
+    ) -> Result<Vec<BillOfMaterials>, ApiError> {
+        let id = project_id.to_string();
+        self.spawn(move |api| api.project_bills_of_materials(&id))
+            .await
+    }
+}
+
+/// Cancels its token when dropped along with an unfinished future, unless disarmed
+struct CancelOnDrop(Option<CancelToken>);
+
+impl CancelOnDrop {
+    fn disarm(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(token) = &self.0 {
+            token.cancel();
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt::{self, Debug, Formatter};
+
+use serde::Deserialize;
+
+use crate::{
+    error::ApiError,
+    transport::{HttpRequest, HttpResponse},
+};
+
+/// Path of the login endpoint, relative to the base url
+pub(crate) const LOGIN_PATH: &str = "rest-auth/login";
+
+/// Username and password of an EC3 account, used to obtain api tokens
+#[derive(Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    key: String,
+}
+
+impl Credentials {
+    pub fn new(username: &str, password: &str) -> Self {
+        Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+}
+
+/// Request logging into the api
+pub(crate) fn login_request(base_url: &str, credentials: &Credentials) -> HttpRequest {
+    let body = serde_json::json!({
+        "username": credentials.username,
+        "password": credentials.password,
+    });
+    HttpRequest::post(&format!("{base_url}{LOGIN_PATH}"), &body.to_string())
+        .header("Content-Type", "application/json")
+}
+
+/// Extract the bearer token from the response to a [`login_request`]
+pub(crate) fn parse_login(response: HttpResponse) -> Result<String, ApiError> {
+    match response.status {
+        _ if response.is_success() => {
+            let login: LoginResponse = serde_json::from_str(&response.body)?;
+            Ok(login.key)
+        }
+        400 | 401 | 403 => Err(ApiError::AuthError),
+        _ => Err(ApiError::RequestError()),
+    }
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    sync::{Arc, Condvar, Mutex, Weak},
+    time::Duration,
+};
+
+/// Token to abort fetches from another thread.
+///
+/// Clones share the same state. Once cancelled, clients holding the token return
+/// [`ApiError::Cancelled`](crate::error::ApiError::Cancelled) before their next request,
+/// page or retry, waking up from any retry delay.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    state: Arc<State>,
+    /// Set on tokens scoped to a single async call, holding the token of the caller if any
+    caller: Option<Option<Box<CancelToken>>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: Mutex<bool>,
+    wakeup: Condvar,
+    children: Mutex<Vec<Weak<State>>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cancel(&self) {
+        self.state.cancel();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        *self.state.cancelled.lock().unwrap()
+    }
+    /// A new token for a single call, cancelled along with the token of the caller if any, and
+    /// which can also be cancelled on its own
+    #[cfg(feature = "async")]
+    pub(crate) fn for_call(caller: Option<&CancelToken>) -> CancelToken {
+        let token = CancelToken {
+            caller: Some(caller.cloned().map(Box::new)),
+            ..Default::default()
+        };
+        if let Some(caller) = caller {
+            let mut children = caller.state.children.lock().unwrap();
+            children.retain(|c| c.strong_count() > 0);
+            children.push(Arc::downgrade(&token.state));
+            drop(children);
+            if caller.is_cancelled() {
+                token.cancel();
+            }
+        }
+        token
+    }
+    /// The token for work outliving the current call, e.g. background refreshes: the token of
+    /// the caller for call tokens, this token otherwise
+    pub(crate) fn outliving_call(&self) -> Option<CancelToken> {
+        match &self.caller {
+            Some(caller) => caller.as_deref().cloned(),
+            None => Some(self.clone()),
+        }
+    }
+    /// Sleep for `duration`, or until cancelled. True if cancelled
+    pub(crate) fn sleep(&self, duration: Duration) -> bool {
+        let guard = self.state.cancelled.lock().unwrap();
+        let (guard, _) = self
+            .state
+            .wakeup
+            .wait_timeout_while(guard, duration, |cancelled| !*cancelled)
+            .unwrap();
+        *guard
+    }
+}
+
+impl State {
+    fn cancel(&self) {
+        *self.cancelled.lock().unwrap() = true;
+        self.wakeup.notify_all();
+        let children = std::mem::take(&mut *self.children.lock().unwrap());
+        for child in children.iter().filter_map(Weak::upgrade) {
+            child.cancel();
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{Manufacturer, Org};
+
+/// Manufacturer organizations indexed by id, name and embedded [`Manufacturer`]
+#[derive(Debug, Clone, Default)]
+pub struct ManufacturerDirectory {
+    orgs: Vec<Org>,
+    by_id: HashMap<String, usize>,
+    by_manufacturer: HashMap<Manufacturer, usize>,
+}
+
+impl ManufacturerDirectory {
+    /// Build a directory, dropping organizations listed more than once
+    pub fn new(orgs: impl IntoIterator<Item = Org>) -> Self {
+        let mut seen = HashSet::new();
+        let orgs: Vec<Org> = orgs
+            .into_iter()
+            .filter(|org| seen.insert(org.clone()))
+            .collect();
+
+        let by_id = orgs
+            .iter()
+            .enumerate()
+            .map(|(i, org)| (org.id.clone(), i))
+            .collect();
+        let mut by_manufacturer = HashMap::new();
+        for (i, org) in orgs.iter().enumerate() {
+            by_manufacturer.entry(org.manufacturer()).or_insert(i);
+        }
+
+        ManufacturerDirectory {
+            orgs,
+            by_id,
+            by_manufacturer,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Org> {
+        self.by_id.get(id).map(|&i| &self.orgs[i])
+    }
+
+    /// Organizations whose name contains `name`, ignoring case
+    pub fn find_by_name(&self, name: &str) -> Vec<&Org> {
+        let name = name.to_lowercase();
+        self.orgs
+            .iter()
+            .filter(|org| org.name.to_lowercase().contains(&name))
+            .collect()
+    }
+
+    /// The organization behind the manufacturer embedded in a material
+    pub fn by_manufacturer(&self, manufacturer: &Manufacturer) -> Option<&Org> {
+        self.by_manufacturer
+            .get(manufacturer)
+            .map(|&i| &self.orgs[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Org> {
+        self.orgs.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.orgs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orgs.is_empty()
+    }
+}
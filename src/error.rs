@@ -5,6 +5,9 @@ pub enum ApiError {
     #[error("Request failed")]
     RequestError(),
 
+    #[error("Could not reach the api: {0}")]
+    TransportError(String),
+
     #[error("Could not read cache")]
     CacheError(#[from] std::io::Error),
 
@@ -25,8 +28,24 @@ pub enum ApiError {
 
     #[error("Wrong Unit format")]
     UnitError,
+
+    #[error("The {0} endpoint does not list materials, run a typed query instead")]
+    NotMaterialsEndpoint(String),
+
+    #[error("Unknown jurisdiction {0}")]
+    UnknownJurisdiction(String),
+
     #[error("Api rejected authentication")]
     AuthError,
     #[error("The client made too many requests to the api")]
     TooManyRequest(String),
+
+    #[error("Fetch was cancelled")]
+    Cancelled,
+
+    #[error("Fetch did not finish before its deadline")]
+    DeadlineExceeded,
+
+    #[error("No cache entry for {0} and the client is offline")]
+    NotCached(String),
 }
@@ -0,0 +1,109 @@
+use std::{
+    borrow::Cow,
+    collections::{HashSet, VecDeque},
+};
+
+use crate::{error::ApiError, is_last_page, models::Ec3Material, Ec3api};
+
+/// A single page of records as returned by the api
+pub(crate) struct Page<T> {
+    pub items: Vec<T>,
+    pub total_pages: Option<usize>,
+    pub total_count: Option<usize>,
+}
+
+/// Iterator over materials that fetches pages from the api on demand.
+///
+/// Created with [`Ec3api::iter_materials`]. Queries are walked one after the other, skipping
+/// materials already yielded. After an error is yielded the iterator is exhausted.
+pub struct MaterialIter<'a> {
+    api: Cow<'a, Ec3api>,
+    path: String,
+    queries: VecDeque<Vec<(&'static str, String)>>,
+    first_page: usize,
+    page_number: usize,
+    max_records: Option<usize>,
+    buffer: VecDeque<Ec3Material>,
+    seen: HashSet<String>,
+    fetched: usize,
+    yielded: usize,
+    done: bool,
+}
+
+impl<'a> MaterialIter<'a> {
+    pub(crate) fn new(
+        api: Cow<'a, Ec3api>,
+        path: String,
+        queries: VecDeque<Vec<(&'static str, String)>>,
+        page_number: usize,
+        max_records: Option<usize>,
+    ) -> Self {
+        MaterialIter {
+            api,
+            path,
+            queries,
+            first_page: page_number,
+            page_number,
+            max_records,
+            buffer: VecDeque::new(),
+            seen: HashSet::new(),
+            fetched: 0,
+            yielded: 0,
+            done: false,
+        }
+    }
+
+    fn next_page(&mut self) -> Result<(), ApiError> {
+        let Some(query) = self.queries.front() else {
+            self.done = true;
+            return Ok(());
+        };
+        let page: Page<Ec3Material> =
+            self.api
+                .fetch_page(&self.path, query, Some(self.page_number))?;
+        let received = page.items.len();
+        self.fetched += received;
+        let seen = &mut self.seen;
+        self.buffer
+            .extend(page.items.into_iter().filter(|m| seen.insert(m.id.clone())));
+
+        if received == 0
+            || is_last_page(
+                self.page_number,
+                self.fetched,
+                page.total_pages,
+                page.total_count,
+            )
+        {
+            // Move on to the next query, if any
+            self.queries.pop_front();
+            self.page_number = self.first_page;
+            self.fetched = 0;
+            self.done = self.queries.is_empty();
+        } else {
+            self.page_number += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for MaterialIter<'_> {
+    type Item = Result<Ec3Material, ApiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_records.is_some_and(|max| self.yielded >= max) {
+            return None;
+        }
+        while self.buffer.is_empty() {
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.next_page() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+        self.yielded += 1;
+        self.buffer.pop_front().map(Ok)
+    }
+}
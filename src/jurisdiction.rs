@@ -0,0 +1,578 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::ApiError;
+
+/// Area materials are sourced from: a UN M49 region, an ISO 3166-1 country or an
+/// ISO 3166-2 subdivision.
+///
+/// Parse from a code or a name, e.g. `"150"`, `"Europe"`, `"DE"`, `"Germany"` or `"DE-BY"`,
+/// or build with [`Jurisdiction::region`], [`Jurisdiction::country`] or
+/// [`Jurisdiction::subdivision`]. Only known codes are accepted, in their canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Jurisdiction(Kind);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Region(u16),
+    Country(&'static str),
+    Subdivision(&'static str),
+}
+
+/// Level of a [`Jurisdiction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JurisdictionKind {
+    /// UN M49 region, e.g. 150 for Europe
+    Region,
+    /// ISO 3166-1 country, e.g. "DE"
+    Country,
+    /// ISO 3166-2 subdivision, e.g. "DE-BY"
+    Subdivision,
+}
+
+impl Jurisdiction {
+    pub const WORLD: Jurisdiction = Jurisdiction(Kind::Region(1));
+    pub(crate) const GERMANY: Jurisdiction = Jurisdiction(Kind::Country("DE"));
+
+    /// UN M49 region with the given code, e.g. 150 for Europe
+    pub fn region(code: u16) -> Result<Jurisdiction, ApiError> {
+        region(code)
+            .map(|r| Jurisdiction(Kind::Region(r.0)))
+            .ok_or_else(|| ApiError::UnknownJurisdiction(code.to_string()))
+    }
+
+    /// ISO 3166-1 country with the given alpha-2 code, in any case. "UK" stands for GB
+    pub fn country(code: &str) -> Result<Jurisdiction, ApiError> {
+        let code = if code.eq_ignore_ascii_case("UK") {
+            "GB"
+        } else {
+            code
+        };
+        country(code)
+            .map(|c| Jurisdiction(Kind::Country(c.0)))
+            .ok_or_else(|| ApiError::UnknownJurisdiction(code.to_string()))
+    }
+
+    /// ISO 3166-2 subdivision with the given code, in any case, e.g. "DE-BY"
+    pub fn subdivision(code: &str) -> Result<Jurisdiction, ApiError> {
+        subdivision(code)
+            .map(|sub| Jurisdiction(Kind::Subdivision(sub.0)))
+            .ok_or_else(|| ApiError::UnknownJurisdiction(code.to_string()))
+    }
+
+    pub fn kind(&self) -> JurisdictionKind {
+        match self.0 {
+            Kind::Region(_) => JurisdictionKind::Region,
+            Kind::Country(_) => JurisdictionKind::Country,
+            Kind::Subdivision(_) => JurisdictionKind::Subdivision,
+        }
+    }
+
+    /// Code as used by the api: the zero-padded M49 code of regions, the ISO code otherwise
+    pub fn code(&self) -> String {
+        self.to_string()
+    }
+
+    /// English name, `None` for codes that are not known
+    pub fn name(&self) -> Option<&'static str> {
+        match self.0 {
+            Kind::Region(code) => region(code).map(|(_, name, _)| *name),
+            Kind::Country(code) => country(code).map(|(_, name, _)| *name),
+            Kind::Subdivision(code) => subdivision(code).map(|(_, name)| *name),
+        }
+    }
+
+    /// The smallest jurisdiction containing this one, `None` for the world
+    pub fn parent(&self) -> Option<Jurisdiction> {
+        match self.0 {
+            Kind::Region(code) => region(code)?.2.map(|r| Jurisdiction(Kind::Region(r))),
+            Kind::Country(code) => country(code).map(|c| Jurisdiction(Kind::Region(c.2))),
+            Kind::Subdivision(code) => {
+                let (country_code, _) = code.split_once('-')?;
+                country(country_code).map(|c| Jurisdiction(Kind::Country(c.0)))
+            }
+        }
+    }
+
+    /// Jurisdictions containing this one, from its parent up to the world
+    pub fn ancestors(&self) -> impl Iterator<Item = Jurisdiction> {
+        std::iter::successors(self.parent(), Jurisdiction::parent)
+    }
+
+    /// True if `other` is this jurisdiction or lies within it, e.g. DE-BY is inside 150
+    pub fn contains(&self, other: &Jurisdiction) -> bool {
+        self == other || other.ancestors().any(|a| a == *self)
+    }
+
+    /// Countries within this jurisdiction
+    pub fn countries(&self) -> Vec<Jurisdiction> {
+        COUNTRIES
+            .iter()
+            .map(|(code, _, _)| Jurisdiction(Kind::Country(code)))
+            .filter(|country| self.contains(country))
+            .collect()
+    }
+}
+
+fn region(code: u16) -> Option<&'static (u16, &'static str, Option<u16>)> {
+    REGIONS.iter().find(|(c, _, _)| *c == code)
+}
+
+fn country(code: &str) -> Option<&'static (&'static str, &'static str, u16)> {
+    COUNTRIES
+        .iter()
+        .find(|(c, _, _)| c.eq_ignore_ascii_case(code))
+}
+
+fn subdivision(code: &str) -> Option<&'static (&'static str, &'static str)> {
+    SUBDIVISIONS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+}
+
+impl FromStr for Jurisdiction {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let unknown = || ApiError::UnknownJurisdiction(s.to_string());
+
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) {
+            let code: u16 = s.parse().map_err(|_| unknown())?;
+            return Jurisdiction::region(code).map_err(|_| unknown());
+        }
+        if let Ok(jurisdiction) = Jurisdiction::country(s).or_else(|_| Jurisdiction::subdivision(s))
+        {
+            return Ok(jurisdiction);
+        }
+
+        let by_name = |name: &str| name.eq_ignore_ascii_case(s);
+        REGIONS
+            .iter()
+            .find(|(_, name, _)| by_name(name))
+            .map(|r| Jurisdiction(Kind::Region(r.0)))
+            .or_else(|| {
+                COUNTRIES
+                    .iter()
+                    .find(|(_, name, _)| by_name(name))
+                    .map(|c| Jurisdiction(Kind::Country(c.0)))
+            })
+            .or_else(|| {
+                SUBDIVISIONS
+                    .iter()
+                    .find(|(_, name)| by_name(name))
+                    .map(|sub| Jurisdiction(Kind::Subdivision(sub.0)))
+            })
+            .ok_or_else(unknown)
+    }
+}
+
+impl Display for Jurisdiction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            Kind::Region(code) => write!(f, "{code:03}"),
+            Kind::Country(code) | Kind::Subdivision(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+impl Serialize for Jurisdiction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Jurisdiction {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// UN M49 regions: code, name, parent region
+const REGIONS: &[(u16, &str, Option<u16>)] = &[
+    (1, "World", None),
+    (2, "Africa", Some(1)),
+    (15, "Northern Africa", Some(2)),
+    (202, "Sub-Saharan Africa", Some(2)),
+    (14, "Eastern Africa", Some(202)),
+    (17, "Middle Africa", Some(202)),
+    (18, "Southern Africa", Some(202)),
+    (11, "Western Africa", Some(202)),
+    (19, "Americas", Some(1)),
+    (419, "Latin America and the Caribbean", Some(19)),
+    (29, "Caribbean", Some(419)),
+    (13, "Central America", Some(419)),
+    (5, "South America", Some(419)),
+    (21, "Northern America", Some(19)),
+    (142, "Asia", Some(1)),
+    (143, "Central Asia", Some(142)),
+    (30, "Eastern Asia", Some(142)),
+    (35, "South-eastern Asia", Some(142)),
+    (34, "Southern Asia", Some(142)),
+    (145, "Western Asia", Some(142)),
+    (150, "Europe", Some(1)),
+    (151, "Eastern Europe", Some(150)),
+    (154, "Northern Europe", Some(150)),
+    (830, "Channel Islands", Some(154)),
+    (39, "Southern Europe", Some(150)),
+    (155, "Western Europe", Some(150)),
+    (9, "Oceania", Some(1)),
+    (53, "Australia and New Zealand", Some(9)),
+    (54, "Melanesia", Some(9)),
+    (57, "Micronesia", Some(9)),
+    (61, "Polynesia", Some(9)),
+];
+
+/// ISO 3166-1 countries: alpha-2 code, name, smallest M49 region
+const COUNTRIES: &[(&str, &str, u16)] = &[
+    // Northern Africa
+    ("DZ", "Algeria", 15),
+    ("EG", "Egypt", 15),
+    ("LY", "Libya", 15),
+    ("MA", "Morocco", 15),
+    ("SD", "Sudan", 15),
+    ("TN", "Tunisia", 15),
+    ("EH", "Western Sahara", 15),
+    // Eastern Africa
+    ("IO", "British Indian Ocean Territory", 14),
+    ("BI", "Burundi", 14),
+    ("KM", "Comoros", 14),
+    ("DJ", "Djibouti", 14),
+    ("ER", "Eritrea", 14),
+    ("ET", "Ethiopia", 14),
+    ("TF", "French Southern Territories", 14),
+    ("KE", "Kenya", 14),
+    ("MG", "Madagascar", 14),
+    ("MW", "Malawi", 14),
+    ("MU", "Mauritius", 14),
+    ("YT", "Mayotte", 14),
+    ("MZ", "Mozambique", 14),
+    ("RE", "Réunion", 14),
+    ("RW", "Rwanda", 14),
+    ("SC", "Seychelles", 14),
+    ("SO", "Somalia", 14),
+    ("SS", "South Sudan", 14),
+    ("UG", "Uganda", 14),
+    ("TZ", "Tanzania", 14),
+    ("ZM", "Zambia", 14),
+    ("ZW", "Zimbabwe", 14),
+    // Middle Africa
+    ("AO", "Angola", 17),
+    ("CM", "Cameroon", 17),
+    ("CF", "Central African Republic", 17),
+    ("TD", "Chad", 17),
+    ("CG", "Congo", 17),
+    ("CD", "Democratic Republic of the Congo", 17),
+    ("GQ", "Equatorial Guinea", 17),
+    ("GA", "Gabon", 17),
+    ("ST", "Sao Tome and Principe", 17),
+    // Southern Africa
+    ("BW", "Botswana", 18),
+    ("SZ", "Eswatini", 18),
+    ("LS", "Lesotho", 18),
+    ("NA", "Namibia", 18),
+    ("ZA", "South Africa", 18),
+    // Western Africa
+    ("BJ", "Benin", 11),
+    ("BF", "Burkina Faso", 11),
+    ("CV", "Cabo Verde", 11),
+    ("CI", "Côte d'Ivoire", 11),
+    ("GM", "Gambia", 11),
+    ("GH", "Ghana", 11),
+    ("GN", "Guinea", 11),
+    ("GW", "Guinea-Bissau", 11),
+    ("LR", "Liberia", 11),
+    ("ML", "Mali", 11),
+    ("MR", "Mauritania", 11),
+    ("NE", "Niger", 11),
+    ("NG", "Nigeria", 11),
+    ("SH", "Saint Helena", 11),
+    ("SN", "Senegal", 11),
+    ("SL", "Sierra Leone", 11),
+    ("TG", "Togo", 11),
+    // Caribbean
+    ("AI", "Anguilla", 29),
+    ("AG", "Antigua and Barbuda", 29),
+    ("AW", "Aruba", 29),
+    ("BS", "Bahamas", 29),
+    ("BB", "Barbados", 29),
+    ("BQ", "Bonaire, Sint Eustatius and Saba", 29),
+    ("VG", "British Virgin Islands", 29),
+    ("KY", "Cayman Islands", 29),
+    ("CU", "Cuba", 29),
+    ("CW", "Curaçao", 29),
+    ("DM", "Dominica", 29),
+    ("DO", "Dominican Republic", 29),
+    ("GD", "Grenada", 29),
+    ("GP", "Guadeloupe", 29),
+    ("HT", "Haiti", 29),
+    ("JM", "Jamaica", 29),
+    ("MQ", "Martinique", 29),
+    ("MS", "Montserrat", 29),
+    ("PR", "Puerto Rico", 29),
+    ("BL", "Saint Barthélemy", 29),
+    ("KN", "Saint Kitts and Nevis", 29),
+    ("LC", "Saint Lucia", 29),
+    ("MF", "Saint Martin", 29),
+    ("VC", "Saint Vincent and the Grenadines", 29),
+    ("SX", "Sint Maarten", 29),
+    ("TT", "Trinidad and Tobago", 29),
+    ("TC", "Turks and Caicos Islands", 29),
+    ("VI", "United States Virgin Islands", 29),
+    // Central America
+    ("BZ", "Belize", 13),
+    ("CR", "Costa Rica", 13),
+    ("SV", "El Salvador", 13),
+    ("GT", "Guatemala", 13),
+    ("HN", "Honduras", 13),
+    ("MX", "Mexico", 13),
+    ("NI", "Nicaragua", 13),
+    ("PA", "Panama", 13),
+    // South America
+    ("AR", "Argentina", 5),
+    ("BO", "Bolivia", 5),
+    ("BV", "Bouvet Island", 5),
+    ("BR", "Brazil", 5),
+    ("CL", "Chile", 5),
+    ("CO", "Colombia", 5),
+    ("EC", "Ecuador", 5),
+    ("FK", "Falkland Islands", 5),
+    ("GF", "French Guiana", 5),
+    ("GY", "Guyana", 5),
+    ("PY", "Paraguay", 5),
+    ("PE", "Peru", 5),
+    ("GS", "South Georgia and the South Sandwich Islands", 5),
+    ("SR", "Suriname", 5),
+    ("UY", "Uruguay", 5),
+    ("VE", "Venezuela", 5),
+    // Northern America
+    ("BM", "Bermuda", 21),
+    ("CA", "Canada", 21),
+    ("GL", "Greenland", 21),
+    ("PM", "Saint Pierre and Miquelon", 21),
+    ("US", "United States", 21),
+    // Central Asia
+    ("KZ", "Kazakhstan", 143),
+    ("KG", "Kyrgyzstan", 143),
+    ("TJ", "Tajikistan", 143),
+    ("TM", "Turkmenistan", 143),
+    ("UZ", "Uzbekistan", 143),
+    // Eastern Asia
+    ("CN", "China", 30),
+    ("HK", "Hong Kong", 30),
+    ("MO", "Macao", 30),
+    ("KP", "North Korea", 30),
+    ("JP", "Japan", 30),
+    ("MN", "Mongolia", 30),
+    ("KR", "South Korea", 30),
+    ("TW", "Taiwan", 30),
+    // South-eastern Asia
+    ("BN", "Brunei Darussalam", 35),
+    ("KH", "Cambodia", 35),
+    ("ID", "Indonesia", 35),
+    ("LA", "Laos", 35),
+    ("MY", "Malaysia", 35),
+    ("MM", "Myanmar", 35),
+    ("PH", "Philippines", 35),
+    ("SG", "Singapore", 35),
+    ("TH", "Thailand", 35),
+    ("TL", "Timor-Leste", 35),
+    ("VN", "Viet Nam", 35),
+    // Southern Asia
+    ("AF", "Afghanistan", 34),
+    ("BD", "Bangladesh", 34),
+    ("BT", "Bhutan", 34),
+    ("IN", "India", 34),
+    ("IR", "Iran", 34),
+    ("MV", "Maldives", 34),
+    ("NP", "Nepal", 34),
+    ("PK", "Pakistan", 34),
+    ("LK", "Sri Lanka", 34),
+    // Western Asia
+    ("AM", "Armenia", 145),
+    ("AZ", "Azerbaijan", 145),
+    ("BH", "Bahrain", 145),
+    ("CY", "Cyprus", 145),
+    ("GE", "Georgia", 145),
+    ("IQ", "Iraq", 145),
+    ("IL", "Israel", 145),
+    ("JO", "Jordan", 145),
+    ("KW", "Kuwait", 145),
+    ("LB", "Lebanon", 145),
+    ("OM", "Oman", 145),
+    ("QA", "Qatar", 145),
+    ("SA", "Saudi Arabia", 145),
+    ("PS", "Palestine", 145),
+    ("SY", "Syria", 145),
+    ("TR", "Türkiye", 145),
+    ("AE", "United Arab Emirates", 145),
+    ("YE", "Yemen", 145),
+    // Eastern Europe
+    ("BY", "Belarus", 151),
+    ("BG", "Bulgaria", 151),
+    ("CZ", "Czechia", 151),
+    ("HU", "Hungary", 151),
+    ("PL", "Poland", 151),
+    ("MD", "Moldova", 151),
+    ("RO", "Romania", 151),
+    ("RU", "Russia", 151),
+    ("SK", "Slovakia", 151),
+    ("UA", "Ukraine", 151),
+    // Northern Europe
+    ("AX", "Åland Islands", 154),
+    ("DK", "Denmark", 154),
+    ("EE", "Estonia", 154),
+    ("FO", "Faroe Islands", 154),
+    ("FI", "Finland", 154),
+    ("GG", "Guernsey", 830),
+    ("IS", "Iceland", 154),
+    ("IE", "Ireland", 154),
+    ("IM", "Isle of Man", 154),
+    ("JE", "Jersey", 830),
+    ("LV", "Latvia", 154),
+    ("LT", "Lithuania", 154),
+    ("NO", "Norway", 154),
+    ("SJ", "Svalbard and Jan Mayen", 154),
+    ("SE", "Sweden", 154),
+    ("GB", "United Kingdom", 154),
+    // Southern Europe
+    ("AL", "Albania", 39),
+    ("AD", "Andorra", 39),
+    ("BA", "Bosnia and Herzegovina", 39),
+    ("HR", "Croatia", 39),
+    ("GI", "Gibraltar", 39),
+    ("GR", "Greece", 39),
+    ("VA", "Holy See", 39),
+    ("IT", "Italy", 39),
+    ("MT", "Malta", 39),
+    ("ME", "Montenegro", 39),
+    ("MK", "North Macedonia", 39),
+    ("PT", "Portugal", 39),
+    ("SM", "San Marino", 39),
+    ("RS", "Serbia", 39),
+    ("SI", "Slovenia", 39),
+    ("ES", "Spain", 39),
+    // Western Europe
+    ("AT", "Austria", 155),
+    ("BE", "Belgium", 155),
+    ("FR", "France", 155),
+    ("DE", "Germany", 155),
+    ("LI", "Liechtenstein", 155),
+    ("LU", "Luxembourg", 155),
+    ("MC", "Monaco", 155),
+    ("NL", "Netherlands", 155),
+    ("CH", "Switzerland", 155),
+    // Australia and New Zealand
+    ("AU", "Australia", 53),
+    ("CX", "Christmas Island", 53),
+    ("CC", "Cocos (Keeling) Islands", 53),
+    ("HM", "Heard Island and McDonald Islands", 53),
+    ("NZ", "New Zealand", 53),
+    ("NF", "Norfolk Island", 53),
+    // Melanesia
+    ("FJ", "Fiji", 54),
+    ("NC", "New Caledonia", 54),
+    ("PG", "Papua New Guinea", 54),
+    ("SB", "Solomon Islands", 54),
+    ("VU", "Vanuatu", 54),
+    // Micronesia
+    ("GU", "Guam", 57),
+    ("KI", "Kiribati", 57),
+    ("MH", "Marshall Islands", 57),
+    ("FM", "Micronesia (Federated States of)", 57),
+    ("NR", "Nauru", 57),
+    ("MP", "Northern Mariana Islands", 57),
+    ("PW", "Palau", 57),
+    ("UM", "United States Minor Outlying Islands", 57),
+    // Polynesia
+    ("AS", "American Samoa", 61),
+    ("CK", "Cook Islands", 61),
+    ("PF", "French Polynesia", 61),
+    ("NU", "Niue", 61),
+    ("PN", "Pitcairn", 61),
+    ("WS", "Samoa", 61),
+    ("TK", "Tokelau", 61),
+    ("TO", "Tonga", 61),
+    ("TV", "Tuvalu", 61),
+    ("WF", "Wallis and Futuna", 61),
+    // Not part of any M49 region
+    ("AQ", "Antarctica", 1),
+];
+
+/// ISO 3166-2 subdivisions: code, name
+const SUBDIVISIONS: &[(&str, &str)] = &[
+    // United States
+    ("US-AL", "Alabama"),
+    ("US-AK", "Alaska"),
+    ("US-AZ", "Arizona"),
+    ("US-AR", "Arkansas"),
+    ("US-CA", "California"),
+    ("US-CO", "Colorado"),
+    ("US-CT", "Connecticut"),
+    ("US-DE", "Delaware"),
+    ("US-DC", "District of Columbia"),
+    ("US-FL", "Florida"),
+    ("US-GA", "Georgia"),
+    ("US-HI", "Hawaii"),
+    ("US-ID", "Idaho"),
+    ("US-IL", "Illinois"),
+    ("US-IN", "Indiana"),
+    ("US-IA", "Iowa"),
+    ("US-KS", "Kansas"),
+    ("US-KY", "Kentucky"),
+    ("US-LA", "Louisiana"),
+    ("US-ME", "Maine"),
+    ("US-MD", "Maryland"),
+    ("US-MA", "Massachusetts"),
+    ("US-MI", "Michigan"),
+    ("US-MN", "Minnesota"),
+    ("US-MS", "Mississippi"),
+    ("US-MO", "Missouri"),
+    ("US-MT", "Montana"),
+    ("US-NE", "Nebraska"),
+    ("US-NV", "Nevada"),
+    ("US-NH", "New Hampshire"),
+    ("US-NJ", "New Jersey"),
+    ("US-NM", "New Mexico"),
+    ("US-NY", "New York"),
+    ("US-NC", "North Carolina"),
+    ("US-ND", "North Dakota"),
+    ("US-OH", "Ohio"),
+    ("US-OK", "Oklahoma"),
+    ("US-OR", "Oregon"),
+    ("US-PA", "Pennsylvania"),
+    ("US-RI", "Rhode Island"),
+    ("US-SC", "South Carolina"),
+    ("US-SD", "South Dakota"),
+    ("US-TN", "Tennessee"),
+    ("US-TX", "Texas"),
+    ("US-UT", "Utah"),
+    ("US-VT", "Vermont"),
+    ("US-VA", "Virginia"),
+    ("US-WA", "Washington"),
+    ("US-WV", "West Virginia"),
+    ("US-WI", "Wisconsin"),
+    ("US-WY", "Wyoming"),
+    // Germany
+    ("DE-BW", "Baden-Württemberg"),
+    ("DE-BY", "Bayern"),
+    ("DE-BE", "Berlin"),
+    ("DE-BB", "Brandenburg"),
+    ("DE-HB", "Bremen"),
+    ("DE-HH", "Hamburg"),
+    ("DE-HE", "Hessen"),
+    ("DE-MV", "Mecklenburg-Vorpommern"),
+    ("DE-NI", "Niedersachsen"),
+    ("DE-NW", "Nordrhein-Westfalen"),
+    ("DE-RP", "Rheinland-Pfalz"),
+    ("DE-SL", "Saarland"),
+    ("DE-SN", "Sachsen"),
+    ("DE-ST", "Sachsen-Anhalt"),
+    ("DE-SH", "Schleswig-Holstein"),
+    ("DE-TH", "Thüringen"),
+];
@@ -1,21 +1,57 @@
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod auth;
+pub mod cancel;
+pub mod directory;
 pub mod error;
+pub mod iter;
+pub mod jurisdiction;
 pub mod material_filter;
 pub mod models;
+pub mod observer;
+pub mod query;
+pub mod rate_limit;
+pub mod retry;
 #[cfg(test)]
 mod tests;
+pub mod transport;
 pub mod utils;
 
+use auth::Credentials;
+use cancel::CancelToken;
+use directory::ManufacturerDirectory;
+use iter::{MaterialIter, Page};
+use jurisdiction::Jurisdiction;
+use log::{debug, info, warn};
 use material_filter::MaterialFilter;
-use models::{DeclaredUnit, Ec3Material};
+use models::{
+    BillOfMaterials, Building, CategoryStats, DeclaredUnit, Ec3Material, Epd, Org, Plant, Project,
+    TaggedMaterial,
+};
+use observer::{Event, Observer};
+use query::{
+    BuildingMaterialsQuery, BuildingsQuery, CategoryQuery, CategoryStatsQuery, EpdQuery,
+    MaterialQuery, OrgQuery, OrgsQuery, PlantQuery, ProjectQuery, ProjectsQuery, Query,
+};
+use rate_limit::RateLimiter;
+use retry::RetryPolicy;
+use serde::{
+    de::{self, DeserializeOwned},
+    Serialize,
+};
 use serde_json::Value;
 use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     fmt::{self, Debug, Display, Formatter},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
+    sync::{Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
-use ureq::{Error, Response};
+use transport::{HttpRequest, HttpResponse, Transport, UreqTransport};
+use utils::CacheMode;
 
 use crate::{
     error::ApiError,
@@ -24,34 +60,79 @@ use crate::{
 };
 
 const BASE_PATH: &str = "https://buildingtransparency.org/api/";
+const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
+const TOTAL_PAGES_HEADER: &str = "X-Total-Pages";
+
+/// Cache entries being refreshed in the background, so each is refreshed once at a time
+static REFRESHING: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Marks a cache entry as refreshed in the background until dropped
+struct Refreshing(PathBuf);
 
-/// Struct that can query the EC3 api for materials
+impl Drop for Refreshing {
+    fn drop(&mut self) {
+        REFRESHING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.0);
+    }
+}
+
+/// Struct that can query the EC3 api for materials.
+///
+/// Clones share the transport and its connection pool, the api token and the rate limiter, so
+/// a client can be configured once and cloned into worker threads. See [`Ec3api::with`] for
+/// overriding settings of a single request.
+#[derive(Clone)]
 pub struct Ec3api {
-    api_key: String,
+    api_key: Arc<Mutex<String>>,
+    credentials: Option<Credentials>,
+    base_url: String,
     endpoint: Endpoint,
-    country: Country,
+    jurisdictions: Vec<Jurisdiction>,
     mf: Option<MaterialFilter>,
     use_cache: bool,
     cache_dir: Option<PathBuf>,
+    cache_mode: CacheMode,
+    cache_ttl: HashMap<Endpoint, Duration>,
+    page_size: Option<usize>,
+    page_number: Option<usize>,
+    all_pages: bool,
+    max_records: Option<usize>,
+    workers: usize,
+    transport: Arc<dyn Transport>,
+    custom_transport: bool,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    observer: Option<Arc<dyn Observer>>,
+    deadline: Option<Duration>,
+    deadline_at: Option<Instant>,
+    cancel_token: Option<CancelToken>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Endpoint {
     Materials,
     Categories,
+    Epds,
+    Plants,
+    Orgs,
+    Projects,
 }
 
-pub enum Country {
-    US,
-    Germany,
-    UK,
-    None,
-}
-
+/// Records of the configured endpoint, returned by [`Ec3api::fetch_all`]. Prefer
+/// [`Ec3api::execute`], whose typed queries know their response type at compile time
 pub enum Ec3Result {
     Materials(Vec<Ec3Material>),
     Categories(Node<Ec3Category>),
+    Epds(Vec<Epd>),
+    Plants(Vec<Plant>),
+    Orgs(Vec<Org>),
+    Projects(Vec<Project>),
 }
 pub type APIResult = Result<Ec3Result, ApiError>;
+/// A filter of [`Ec3api::fetch_batch`] along with its materials
+pub type BatchResult = (MaterialFilter, Result<Vec<Ec3Material>, ApiError>);
 
 /*
 * Impl for EC3Api
@@ -59,17 +140,75 @@ pub type APIResult = Result<Ec3Result, ApiError>;
 impl Ec3api {
     pub fn new(api_key: &str) -> Ec3api {
         Ec3api {
-            api_key: api_key.to_string(),
+            api_key: Arc::new(Mutex::new(api_key.to_string())),
+            credentials: None,
+            base_url: BASE_PATH.to_string(),
             endpoint: Endpoint::Materials,
-            country: Country::Germany,
+            jurisdictions: vec![Jurisdiction::GERMANY],
             mf: None,
             cache_dir: None,
             use_cache: true,
+            cache_mode: CacheMode::default(),
+            cache_ttl: HashMap::new(),
+            page_size: None,
+            page_number: None,
+            all_pages: false,
+            max_records: None,
+            workers: 4,
+            transport: UreqTransport::shared(),
+            custom_transport: false,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            observer: None,
+            deadline: None,
+            deadline_at: None,
+            cancel_token: None,
         }
     }
 
-    pub fn country(&mut self, country_code: Country) -> &mut Self {
-        self.country = country_code;
+    /// Create a client that logs in with an EC3 account on the first request,
+    /// and logs in again once when the api rejects an expired token
+    pub fn with_credentials(username: &str, password: &str) -> Ec3api {
+        let mut api = Ec3api::new("");
+        api.credentials = Some(Credentials::new(username, password));
+        api
+    }
+
+    /// Log in with the configured credentials and store the obtained token
+    pub fn login(&self) -> Result<(), ApiError> {
+        let credentials = self.credentials.as_ref().ok_or(ApiError::AuthError)?;
+        let response = self.send(&auth::login_request(&self.base_url, credentials))?;
+        let token = auth::parse_login(response)?;
+        *self.api_key.lock().unwrap() = token;
+        Ok(())
+    }
+
+    /// A copy of this client with some settings overridden, e.g.
+    /// `api.with(|req| { req.page_size(10); }).fetch()`. The copy shares the connection pool,
+    /// api token and rate limiter of this client.
+    pub fn with(&self, configure: impl FnOnce(&mut Ec3api)) -> Ec3api {
+        let mut api = self.clone();
+        configure(&mut api);
+        api
+    }
+
+    /// Only query materials from this jurisdiction. Defaults to Germany
+    pub fn jurisdiction(&mut self, jurisdiction: Jurisdiction) -> &mut Self {
+        self.jurisdictions = vec![jurisdiction];
+
+        self
+    }
+
+    /// Query materials from several jurisdictions, one query each, merging the results
+    pub fn jurisdictions(&mut self, jurisdictions: Vec<Jurisdiction>) -> &mut Self {
+        self.jurisdictions = jurisdictions;
+
+        self
+    }
+
+    /// Query materials from every jurisdiction
+    pub fn any_jurisdiction(&mut self) -> &mut Self {
+        self.jurisdictions.clear();
 
         self
     }
@@ -84,14 +223,94 @@ impl Ec3api {
 
         self
     }
+    /// Set the transport used to send every request, e.g. a [`transport::MockTransport`]
+    pub fn transport(&mut self, transport: Arc<dyn Transport>) -> &mut Self {
+        self.transport = transport;
+        self.custom_transport = true;
+        self
+    }
+    /// Set the root of the api, e.g. a caching proxy or a mock server on localhost.
+    /// Defaults to `https://buildingtransparency.org/api/`
+    pub fn base_url(&mut self, url: &str) -> &mut Self {
+        self.base_url = url.to_string();
+        if !self.base_url.ends_with('/') {
+            self.base_url.push('/');
+        }
+        self
+    }
+    /// Set how failed requests are retried, for every endpoint
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = policy;
+        self
+    }
+    /// Throttle requests with a limiter, possibly shared with other clients
+    pub fn rate_limiter(&mut self, limiter: Arc<RateLimiter>) -> &mut Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+    /// Give up connecting or reading from the api after the given timeouts. Defaults to 30 and
+    /// 60 seconds. Only applies to the default transport: one set with [`Ec3api::transport`],
+    /// before or after, keeps its own timeouts
+    pub fn timeouts(&mut self, connect: Duration, read: Duration) -> &mut Self {
+        if self.custom_transport {
+            warn!("timeouts ignored by the custom transport");
+        } else {
+            self.transport = Arc::new(UreqTransport::with_timeouts(connect, read));
+        }
+        self
+    }
+    /// Fail with [`ApiError::DeadlineExceeded`] once an operation, e.g. a fetch or a walk of
+    /// [`Ec3api::iter_materials`], has run for longer than `timeout`, retries included
+    pub fn deadline(&mut self, timeout: Duration) -> &mut Self {
+        self.deadline = Some(timeout);
+        self
+    }
+    /// Fail with [`ApiError::Cancelled`] once `token` is cancelled
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.cancel_token = Some(token);
+        self
+    }
+    /// Report the events of this client, e.g. for progress bars or metrics
+    pub fn observer(&mut self, observer: Arc<dyn Observer>) -> &mut Self {
+        self.observer = Some(observer);
+        self
+    }
+    fn emit(&self, event: Event) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event);
+        }
+    }
+    fn cache_hit(&self, cache_key: &str) {
+        debug!(cache_key; "cache hit");
+        self.emit(Event::CacheHit { cache_key });
+    }
+    fn cache_miss(&self, cache_key: &str) {
+        debug!(cache_key; "no cache found");
+        self.emit(Event::CacheMiss { cache_key });
+    }
     fn prepare_url(&self) -> String {
-        let jurisdiction = match self.country {
-            Country::None => "".to_owned(),
-            _ => format!("?jurisdiction={}", self.country),
-        };
-        let url = format!("{}{}{}", BASE_PATH, self.endpoint, jurisdiction);
-
-        url
+        format!("{}{}", self.base_url, self.endpoint)
+    }
+    /// Query parameters shared by every request: the jurisdiction and the material filter
+    fn prepare_query(
+        &self,
+        filter: &str,
+        jurisdiction: Option<&Jurisdiction>,
+    ) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(jurisdiction) = jurisdiction {
+            query.push(("jurisdiction", jurisdiction.code()));
+        }
+        query.push(("mf", filter.to_string()));
+        query
+    }
+    /// The jurisdictions to run a query for, `None` standing for any jurisdiction
+    fn query_jurisdictions(&self) -> Vec<Option<&Jurisdiction>> {
+        if self.jurisdictions.is_empty() {
+            vec![None]
+        } else {
+            self.jurisdictions.iter().map(Some).collect()
+        }
     }
     /// True to read and write into cache
     pub fn use_cache(&mut self, opt: bool) -> &mut Self {
@@ -104,43 +323,239 @@ impl Ec3api {
         self.use_cache = true;
         self
     }
-    fn get_cached(&mut self) -> Option<Ec3Result> {
-        if let Some(cache_dir) = &self.cache_dir {
+    /// Treat cache entries of `endpoint` fetched at least `ttl` ago as stale. Entries don't
+    /// expire by default
+    pub fn cache_ttl(&mut self, endpoint: Endpoint, ttl: Duration) -> &mut Self {
+        self.cache_ttl.insert(endpoint, ttl);
+        self
+    }
+    /// Set how stale cache entries are treated, or work offline from the cache
+    pub fn cache_mode(&mut self, mode: CacheMode) -> &mut Self {
+        self.cache_mode = mode;
+        self
+    }
+    /// Number of records requested per page
+    pub fn page_size(&mut self, size: usize) -> &mut Self {
+        self.page_size = Some(size);
+        self
+    }
+    /// Page to request, starting at 1. When walking all pages, the first page fetched.
+    pub fn page_number(&mut self, page: usize) -> &mut Self {
+        self.page_number = Some(page);
+        self
+    }
+    /// True to walk every page of the results and concatenate them
+    pub fn all_pages(&mut self, opt: bool) -> &mut Self {
+        self.all_pages = opt;
+        self
+    }
+    /// Stop fetching once this many records have been collected
+    pub fn max_records(&mut self, max: usize) -> &mut Self {
+        self.max_records = Some(max);
+        self
+    }
+    /// Maximum number of filters fetched at once by [`Ec3api::fetch_batch`]. Defaults to 4
+    pub fn workers(&mut self, workers: usize) -> &mut Self {
+        self.workers = workers.max(1);
+        self
+    }
+    fn get_cached(&self) -> Option<Ec3Result> {
+        if self.cache_dir.is_some() {
             match self.endpoint {
                 Endpoint::Materials => {
-                    if let Some(mf) = &self.mf {
-                        let category = mf.get_category();
-
-                        if let Ok(ret) = utils::read_cache(cache_dir, &category) {
-                            return Some(Ec3Result::Materials(ret));
-                        } else {
-                            println!("no cache found");
-                            return None;
-                        }
+                    if self.mf.is_some() {
+                        let (cache_key, _) = self.cache_key();
+                        return self
+                            .read_cached(
+                                Endpoint::Materials,
+                                &cache_key,
+                                utils::read_cache,
+                                |api| api.fetch().map(drop),
+                            )
+                            .map(Ec3Result::Materials);
                     } else {
-                        eprintln!("Using cache requires specifying a MaterialFilter");
+                        warn!("Using cache requires specifying a MaterialFilter");
                         return None;
                     }
                 }
                 Endpoint::Categories => {
-                    println!("No cache for Categories.");
+                    debug!("No cache for Categories");
+                    return None;
+                }
+                Endpoint::Epds | Endpoint::Plants | Endpoint::Orgs | Endpoint::Projects => {
+                    debug!(endpoint:% = self.endpoint; "No cache for endpoint");
                     return None;
                 }
             }
         }
         None
     }
-    pub fn fetch_all(&mut self) -> APIResult {
+    pub fn fetch_all(&self) -> APIResult {
+        if let Some(api) = self.start_operation() {
+            return api.fetch_all();
+        }
         if self.use_cache {
             if let Some(cached) = self.get_cached() {
                 return Ok(cached);
             }
         }
-        println!("Querying {}...", &self.endpoint);
+        // Materials are paged and queried per jurisdiction, like `fetch`. The other endpoints
+        // take neither jurisdictions nor material filters, and listings are walked to the end
+        let path = self.endpoint.to_string();
+        match self.endpoint {
+            Endpoint::Materials => Ok(Ec3Result::Materials(self.fetch_uncached()?)),
+
+            Endpoint::Categories => Ok(Ec3Result::Categories(get_categories(
+                self.get_json(&path, &[])?,
+            )?)),
+
+            Endpoint::Epds => Ok(Ec3Result::Epds(self.fetch_list(&path, &[], true)?)),
+
+            Endpoint::Plants => Ok(Ec3Result::Plants(self.fetch_list(&path, &[], true)?)),
 
+            Endpoint::Orgs => Ok(Ec3Result::Orgs(self.fetch_list(&path, &[], true)?)),
+
+            Endpoint::Projects => Ok(Ec3Result::Projects(self.fetch_list(&path, &[], true)?)),
+        }
+    }
+    /// Fetch the materials matching the filter and jurisdictions, using the cache. Fails with
+    /// [`ApiError::NotMaterialsEndpoint`] unless the endpoint is [`Endpoint::Materials`]; other
+    /// endpoints are read with [`Ec3api::execute`] or [`Ec3api::fetch_all`]
+    pub fn fetch(&self) -> Result<Vec<Ec3Material>, error::ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.fetch();
+        }
+        self.check_materials_endpoint()?;
+        let (cache_key, query) = self.cache_key();
+
+        if let Some(ret) = self.read_cached(self.endpoint, &cache_key, utils::read_cache, |api| {
+            api.fetch().map(drop)
+        }) {
+            return Ok(ret);
+        }
+        let mats = self.fetch_uncached()?;
+
+        self.write_cached(&cache_key, &mats, query);
+        Ok(mats)
+    }
+
+    fn check_materials_endpoint(&self) -> Result<(), ApiError> {
+        match self.endpoint {
+            Endpoint::Materials => Ok(()),
+            endpoint => Err(ApiError::NotMaterialsEndpoint(endpoint.to_string())),
+        }
+    }
+
+    /// Key of the cache entry of the configured query, along with the query it stands for.
+    ///
+    /// The key is the category, or the endpoint without a filter, followed by a stable hash of
+    /// the endpoint, jurisdictions, full filter including pragmas, and paging settings.
+    fn cache_key(&self) -> (String, Value) {
+        let query = serde_json::json!({
+            "endpoint": self.endpoint.to_string(),
+            "jurisdictions": self.jurisdictions.iter().map(Jurisdiction::code).collect::<Vec<_>>(),
+            "filter": self.mf.as_ref().map(convert),
+            "page_size": self.page_size,
+            "page_number": self.page_number,
+            "all_pages": self.all_pages,
+            "max_records": self.max_records,
+        });
+        let name = match &self.mf {
+            Some(mf) => mf.get_category(),
+            None => self.endpoint.to_string().replace('/', "_"),
+        };
+        let hash = utils::stable_hash(&query.to_string());
+        (format!("{name}_{hash:016x}"), query)
+    }
+
+    /// Fetch the materials of many filters concurrently, on at most `workers` threads.
+    ///
+    /// Each filter is fetched like [`Ec3api::fetch`], sharing the cache, retry policy and rate
+    /// limiter of this client. Returns each filter with its result, in the order given; a
+    /// failed filter doesn't stop the others.
+    pub fn fetch_batch(&self, filters: Vec<MaterialFilter>) -> Vec<BatchResult> {
+        if let Some(api) = self.start_operation() {
+            return api.fetch_batch(filters);
+        }
+        let workers = self.workers.min(filters.len());
+        let mut results: Vec<Option<BatchResult>> = Vec::new();
+        results.resize_with(filters.len(), || None);
+        let queue = Mutex::new(filters.into_iter().enumerate().collect::<VecDeque<_>>());
+        let results = Mutex::new(results);
+
+        thread::scope(|s| {
+            for _ in 0..workers {
+                s.spawn(|| loop {
+                    let Some((i, mf)) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let api = self.with(|req| {
+                        req.material_filter(mf.clone());
+                    });
+                    let result = api.fetch();
+                    if let Err(e) = &result {
+                        warn!(category = mf.get_category().as_str(); "batch fetch failed: {e}");
+                    }
+                    results.lock().unwrap()[i] = Some((mf, result));
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("Every filter is fetched"))
+            .collect()
+    }
+
+    /// Fetch the materials of every configured jurisdiction, each tagged with the jurisdiction
+    /// whose query returned it. Materials returned for several jurisdictions are kept once,
+    /// tagged with the first of them. The cache is not used.
+    pub fn fetch_by_jurisdiction(&self) -> Result<Vec<TaggedMaterial>, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.fetch_by_jurisdiction();
+        }
+        self.check_materials_endpoint()?;
         let path = self.prepare_url();
+        let filter = if let Some(mf) = &self.mf {
+            convert(mf)
+        } else {
+            String::new()
+        };
 
-        let auth = format!("Bearer {}", self.api_key);
+        let mut seen = HashSet::new();
+        let mut tagged = Vec::new();
+        for jurisdiction in self.query_jurisdictions() {
+            info!(
+                endpoint:% = self.endpoint, url = path.as_str(), jurisdiction:? = jurisdiction;
+                "querying"
+            );
+            let query = self.prepare_query(&filter, jurisdiction);
+            let materials: Vec<Ec3Material> = self.fetch_configured_pages(&path, &query)?;
+            tagged.extend(
+                materials
+                    .into_iter()
+                    .filter(|m| seen.insert(m.id.clone()))
+                    .map(|material| TaggedMaterial {
+                        jurisdiction: jurisdiction.copied().unwrap_or(Jurisdiction::WORLD),
+                        material,
+                    }),
+            );
+        }
+        if let Some(max) = self.max_records {
+            tagged.truncate(max);
+        }
+        Ok(tagged)
+    }
+
+    /// Fetch the materials of every configured jurisdiction, bypassing the cache
+    fn fetch_uncached(&self) -> Result<Vec<Ec3Material>, ApiError> {
+        if self.jurisdictions.len() > 1 {
+            let tagged = self.fetch_by_jurisdiction()?;
+            return Ok(tagged.into_iter().map(|t| t.material).collect());
+        }
+        let path = self.prepare_url();
+        info!(endpoint:% = self.endpoint, url = path.as_str(); "querying");
 
         let filter = if let Some(mf) = &self.mf {
             convert(mf)
@@ -148,117 +563,599 @@ impl Ec3api {
             String::new()
         };
 
-        let response = ureq::get(&path)
-            .set("Authorization", &auth)
-            .query("mf", &filter)
-            .call()
-            .map_err(|e| {
-                eprintln!("{e}");
-                match e {
-                    ureq::Error::Status(401, _) => ApiError::AuthError,
-                    ureq::Error::Status(_, _) => ApiError::RequestError(),
-                    ureq::Error::Transport(_) => ApiError::RequestError(),
-                }
-            })?
-            .into_string()?;
+        let query = self.prepare_query(&filter, self.jurisdictions.first());
+        self.fetch_configured_pages(&path, &query)
+    }
 
-        let json: Value =
-            serde_json::from_str(&response).map_err(ApiError::DeserializationError)?;
-        match self.endpoint {
-            Endpoint::Materials => Ok(Ec3Result::Materials(get_materials(json)?)),
+    /// Fetch the configured endpoint as raw json, bypassing the cache. List endpoints give an
+    /// array holding the records of every configured jurisdiction and page.
+    pub fn fetch_raw(&self) -> Result<Value, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.fetch_raw();
+        }
+        if matches!(self.endpoint, Endpoint::Categories) {
+            return self.get_json(&self.endpoint.to_string(), &[]);
+        }
+        let path = self.prepare_url();
+        let filter = self.mf.as_ref().map(convert).unwrap_or_default();
 
-            Endpoint::Categories => Ok(Ec3Result::Categories(get_categories(json)?)),
+        let mut records: Vec<Value> = Vec::new();
+        for jurisdiction in self.query_jurisdictions() {
+            info!(
+                endpoint:% = self.endpoint, url = path.as_str(), jurisdiction:? = jurisdiction;
+                "querying"
+            );
+            let query = self.prepare_query(&filter, jurisdiction);
+            records.extend(self.fetch_configured_pages::<Value>(&path, &query)?);
         }
+        if let Some(max) = self.max_records {
+            records.truncate(max);
+        }
+        Ok(Value::Array(records))
     }
-    pub fn fetch(&mut self) -> Result<Vec<Ec3Material>, error::ApiError> {
-        let category = match &self.mf {
-            Some(mf) => mf.get_category(),
-            None => "cache".to_string(),
+
+    /// Fetch the configured endpoint into a custom type, e.g. `Vec<MyMaterial>` to read fields
+    /// not modelled by this crate
+    pub fn fetch_as<T: DeserializeOwned>(&self) -> Result<T, ApiError> {
+        serde_json::from_value(self.fetch_raw()?).map_err(ApiError::DeserializationError)
+    }
+
+    /// Fetch a single material by id. Uses the cache like [`Ec3api::fetch`]
+    pub fn material_by_id(&self, id: &str) -> Result<Ec3Material, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.material_by_id(id);
+        }
+        let cache_key = format!("material_{id}");
+        let read = |path: &Path, key: &str| {
+            utils::read_cache(path, key)?
+                .pop()
+                .ok_or(ApiError::EmptyArray())
         };
+        let refresh_id = id.to_string();
+        let refresh = move |api: &Ec3api| api.material_by_id(&refresh_id).map(drop);
+        if let Some(material) = self.read_cached(Endpoint::Materials, &cache_key, read, refresh) {
+            return Ok(material);
+        }
 
-        if self.use_cache {
-            if let Some(path) = &self.cache_dir {
-                if let Ok(ret) = utils::read_cache(path, &category) {
-                    return Ok(ret);
-                } else {
-                    println!("no cache found");
+        let material = self.execute(&MaterialQuery { id: id.to_string() })?;
+        let query = serde_json::json!({ "endpoint": Endpoint::Materials.to_string(), "id": id });
+        self.write_cached(&cache_key, &[&material], query);
+        Ok(material)
+    }
+
+    /// Fetch a single category by id, along with its subcategories. Uses the cache like
+    /// [`Ec3api::fetch`]
+    pub fn category_by_id(&self, id: &str) -> Result<Node<Ec3Category>, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.category_by_id(id);
+        }
+        let cache_key = format!("category_{id}");
+        let refresh_id = id.to_string();
+        let refresh = move |api: &Ec3api| api.category_by_id(&refresh_id).map(drop);
+        if let Some(category) = self.read_cached(
+            Endpoint::Categories,
+            &cache_key,
+            utils::read_cache_as,
+            refresh,
+        ) {
+            return Ok(category);
+        }
+
+        let category = self.execute(&CategoryQuery { id: id.to_string() })?;
+        let query = serde_json::json!({ "endpoint": Endpoint::Categories.to_string(), "id": id });
+        self.write_cached(&cache_key, &category, query);
+        Ok(category)
+    }
+
+    /// Fetch the GWP statistics and baselines of the category with the given
+    /// [`Ec3Category::id`], without its materials
+    pub fn category_stats(&self, category_id: &str) -> Result<CategoryStats, ApiError> {
+        self.execute(&CategoryStatsQuery {
+            id: category_id.to_string(),
+        })
+    }
+
+    /// Read the cache entry `cache_key` of `endpoint`, if the cache is used.
+    ///
+    /// Entries older than the ttl of the endpoint are skipped, served while `refresh` runs in
+    /// the background, or served anyway, depending on the cache mode.
+    fn read_cached<T>(
+        &self,
+        endpoint: Endpoint,
+        cache_key: &str,
+        read: impl FnOnce(&Path, &str) -> Result<T, ApiError>,
+        refresh: impl FnOnce(&Ec3api) -> Result<(), ApiError> + Send + 'static,
+    ) -> Option<T> {
+        let path = self.cache_dir.as_ref().filter(|_| self.use_cache)?;
+        let Ok(value) = read(path, cache_key) else {
+            self.cache_miss(cache_key);
+            return None;
+        };
+        let stale = self
+            .cache_ttl
+            .get(&endpoint)
+            .is_some_and(|ttl| utils::cache_age(path, cache_key).is_none_or(|age| age >= *ttl));
+        if !stale {
+            self.cache_hit(cache_key);
+            return Some(value);
+        }
+        match self.cache_mode {
+            CacheMode::Fresh => {
+                debug!(cache_key; "cache entry expired");
+                self.cache_miss(cache_key);
+                None
+            }
+            CacheMode::StaleWhileRevalidate => {
+                self.cache_hit(cache_key);
+                let entry = path.join(cache_key);
+                let inserted = REFRESHING
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(entry.clone());
+                if !inserted {
+                    debug!(cache_key; "serving stale cache entry, already refreshing it");
+                    return Some(value);
                 }
+                debug!(cache_key; "serving stale cache entry while refreshing it");
+                let mut api = self.clone();
+                api.use_cache = false;
+                api.deadline_at = None;
+                api.cancel_token = self
+                    .cancel_token
+                    .as_ref()
+                    .and_then(CancelToken::outliving_call);
+                thread::spawn(move || {
+                    // Dropped even if the refresh panics, so the entry can be refreshed again
+                    let _refreshing = Refreshing(entry);
+                    if let Err(e) = refresh(&api) {
+                        warn!("could not refresh stale cache entry: {e}");
+                    }
+                });
+                Some(value)
+            }
+            CacheMode::OnlyIfCached => {
+                self.cache_hit(cache_key);
+                Some(value)
             }
         }
-        println!("Querying {}...", &self.endpoint);
+    }
 
-        let path = self.prepare_url();
+    /// Write `value` into the cache dir, if any, and record the `query` it answers in the index
+    fn write_cached<T: Serialize + ?Sized>(&self, cache_key: &str, value: &T, query: Value) {
+        if let Some(path) = &self.cache_dir {
+            match serde_json::to_string_pretty(value) {
+                Ok(json) => {
+                    utils::write_cache(path, json, cache_key);
+                    utils::write_cache_index(path, cache_key, query);
+                }
+                Err(e) => {
+                    warn!(cache_key; "could not write cache: {e}");
+                }
+            };
+        }
+    }
+
+    /// Fetch the EPD document with the given id
+    pub fn epd(&self, id: &str) -> Result<Epd, ApiError> {
+        self.execute(&EpdQuery { id: id.to_string() })
+    }
+
+    /// Fetch the EPD document a material was declared in, by its open xpd uuid. `None` if the
+    /// material doesn't reference one
+    pub fn epd_of(&self, material: &Ec3Material) -> Result<Option<Epd>, ApiError> {
+        match &material.open_xpd_uuid {
+            Some(uuid) => self.epd(uuid).map(Some),
+            None => Ok(None),
+        }
+    }
 
-        let auth = format!("Bearer {}", self.api_key);
+    /// Fetch the manufacturing plant with the given id
+    pub fn plant(&self, id: &str) -> Result<Plant, ApiError> {
+        self.execute(&PlantQuery { id: id.to_string() })
+    }
+
+    /// Fetch the plant a material was produced at, `None` if the material is not linked to one
+    pub fn plant_of(&self, material: &Ec3Material) -> Result<Option<Plant>, ApiError> {
+        match &material.plant_or_group {
+            Some(plant) => self.plant(&plant.id).map(Some),
+            None => Ok(None),
+        }
+    }
 
+    /// Fetch the organization with the given id
+    pub fn org(&self, id: &str) -> Result<Org, ApiError> {
+        self.execute(&OrgQuery { id: id.to_string() })
+    }
+
+    /// Fetch every organization whose name contains `name`
+    pub fn orgs_by_name(&self, name: &str) -> Result<Vec<Org>, ApiError> {
+        self.execute(&OrgsQuery {
+            name: Some(name.to_string()),
+        })
+    }
+
+    /// Fetch every organization into a directory for lookups by id, name or manufacturer
+    pub fn manufacturer_directory(&self) -> Result<ManufacturerDirectory, ApiError> {
+        let orgs = self.execute(&OrgsQuery::default())?;
+        Ok(ManufacturerDirectory::new(orgs))
+    }
+
+    /// Fetch every project of the account
+    pub fn projects(&self) -> Result<Vec<Project>, ApiError> {
+        self.execute(&ProjectsQuery)
+    }
+
+    pub fn project(&self, id: &str) -> Result<Project, ApiError> {
+        self.execute(&ProjectQuery { id: id.to_string() })
+    }
+
+    /// Fetch the buildings of a project
+    pub fn buildings(&self, project_id: &str) -> Result<Vec<Building>, ApiError> {
+        self.execute(&BuildingsQuery {
+            project_id: project_id.to_string(),
+        })
+    }
+
+    /// Fetch the material quantities assigned to a building
+    pub fn bill_of_materials(&self, building: &Building) -> Result<BillOfMaterials, ApiError> {
+        let items = self.execute(&BuildingMaterialsQuery {
+            building_id: building.id.clone(),
+        })?;
+        Ok(BillOfMaterials {
+            building: building.clone(),
+            items,
+        })
+    }
+
+    /// Fetch the bill of materials of every building of a project
+    pub fn project_bills_of_materials(
+        &self,
+        project_id: &str,
+    ) -> Result<Vec<BillOfMaterials>, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.project_bills_of_materials(project_id);
+        }
+        self.buildings(project_id)?
+            .iter()
+            .map(|building| self.bill_of_materials(building))
+            .collect()
+    }
+
+    /// Run a typed query, e.g. `api.execute(&MaterialsQuery::default())`
+    pub fn execute<Q: Query>(&self, query: &Q) -> Result<Q::Output, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.execute(query);
+        }
+        query.execute(self)
+    }
+
+    /// Fetch the json at `path`, relative to the base url
+    pub fn get_json(&self, path: &str, params: &[(&str, String)]) -> Result<Value, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.get_json(path, params);
+        }
+        let url = format!("{}{}", self.base_url, path);
+        info!(url = url.as_str(); "querying");
+        let response = self.get_response(&url, params)?;
+        serde_json::from_str(&response.body).map_err(ApiError::DeserializationError)
+    }
+
+    /// Fetch the list at `path`, relative to the base url: the first page, or every page if
+    /// `all_pages` is true. The `page_number` and `max_records` of the client don't apply
+    pub fn fetch_list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+        all_pages: bool,
+    ) -> Result<Vec<T>, ApiError> {
+        if let Some(api) = self.start_operation() {
+            return api.fetch_list(path, params, all_pages);
+        }
+        let url = format!("{}{}", self.base_url, path);
+        info!(url = url.as_str(); "querying");
+        self.fetch_pages(&url, params, None, all_pages, None)
+    }
+
+    /// Fetch the list at `path`, relative to the base url, paging as configured on the client
+    pub(crate) fn fetch_configured_list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<Vec<T>, ApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        info!(url = url.as_str(); "querying");
+        self.fetch_configured_pages(&url, params)
+    }
+
+    /// Lazily iterate over every material matching the query, fetching pages on demand.
+    /// Starts at `page_number` and stops after `max_records`. The cache is not used.
+    /// With several jurisdictions, each is queried in turn and duplicates are skipped.
+    pub fn iter_materials(&self) -> MaterialIter<'_> {
         let filter = if let Some(mf) = &self.mf {
             convert(mf)
         } else {
             String::new()
         };
+        let queries = self
+            .query_jurisdictions()
+            .into_iter()
+            .map(|jurisdiction| self.prepare_query(&filter, jurisdiction))
+            .collect();
+        // The deadline counts from the creation of the iterator
+        let api = match self.start_operation() {
+            Some(api) => Cow::Owned(api),
+            None => Cow::Borrowed(self),
+        };
+        MaterialIter::new(
+            api,
+            self.prepare_url(),
+            queries,
+            self.page_number.unwrap_or(1),
+            self.max_records,
+        )
+    }
 
-        let response = get_response(&path, &auth, &filter)
-            .map_err(|e| {
-                eprintln!("{e}");
-                match e {
-                    ureq::Error::Status(401, _) => ApiError::AuthError,
-                    ureq::Error::Status(429, response) => {
-                        ApiError::TooManyRequest(response.into_string().unwrap_or("".to_string()))
-                    }
-                    ureq::Error::Status(_, _) => ApiError::RequestError(),
-                    ureq::Error::Transport(_) => ApiError::RequestError(),
+    /// Fetch pages with the `page_number`, `all_pages` and `max_records` of the client
+    fn fetch_configured_pages<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>, ApiError> {
+        self.fetch_pages(
+            path,
+            query,
+            self.page_number,
+            self.all_pages,
+            self.max_records,
+        )
+    }
+
+    /// Fetch `first_page`, defaulting to the first, or every page from there when `all_pages`
+    /// is set. Stops once `max_records` have been collected
+    fn fetch_pages<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        first_page: Option<usize>,
+        all_pages: bool,
+        max_records: Option<usize>,
+    ) -> Result<Vec<T>, ApiError> {
+        let started = Instant::now();
+        let mut records: Vec<T> = Vec::new();
+        let mut page_number = first_page.unwrap_or(1);
+
+        loop {
+            self.check_cancelled()?;
+            let page_param = (all_pages || first_page.is_some()).then_some(page_number);
+            let page = self.fetch_page(path, query, page_param)?;
+            let received = page.items.len();
+            records.extend(page.items);
+
+            if let Some(max) = max_records {
+                if records.len() >= max {
+                    records.truncate(max);
+                    break;
                 }
-            })?
-            .into_string()?;
+            }
+            if !all_pages
+                || received == 0
+                || is_last_page(
+                    page_number,
+                    records.len(),
+                    page.total_pages,
+                    page.total_count,
+                )
+            {
+                break;
+            }
+            page_number += 1;
+        }
+        self.emit(Event::Finished {
+            url: path,
+            records: records.len(),
+            elapsed: started.elapsed(),
+        });
+        Ok(records)
+    }
 
-        let json: Value = serde_json::from_str(&response)?;
+    /// Fetch a single page of records along with the paging headers of the response
+    pub(crate) fn fetch_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+        page_number: Option<usize>,
+    ) -> Result<Page<T>, ApiError> {
+        let mut query = query.to_vec();
+        if let Some(size) = self.page_size {
+            query.push(("page_size", size.to_string()));
+        }
+        if let Some(page_number) = page_number {
+            query.push(("page_number", page_number.to_string()));
+        }
 
-        let mats = get_materials(json)?;
+        let response = self.get_response(path, &query)?;
 
-        let category = match &self.mf {
-            Some(mf) => mf.get_category(),
-            None => "cache".to_string(),
-        };
-        if let Some(path) = &self.cache_dir {
-            match serde_json::to_string_pretty(&mats) {
-                Ok(json) => utils::write_cache(path, json, &category),
-                Err(e) => {
-                    eprint!("Error: could not write cache: {e:?}");
+        let total_pages: Option<usize> = response
+            .header(TOTAL_PAGES_HEADER)
+            .and_then(|h| h.parse().ok());
+        let total_count: Option<usize> = response
+            .header(TOTAL_COUNT_HEADER)
+            .and_then(|h| h.parse().ok());
+
+        let json: Value = serde_json::from_str(&response.body)?;
+
+        let items: Vec<T> = get_list(json)?;
+        self.emit(Event::PageReceived {
+            url: path,
+            page_number,
+            records: items.len(),
+            total_pages,
+            total_count,
+        });
+        Ok(Page {
+            items,
+            total_pages,
+            total_count,
+        })
+    }
+
+    /// Fetch and retry according to the retry policy
+    fn get_response(&self, path: &str, query: &[(&str, String)]) -> Result<HttpResponse, ApiError> {
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            self.check_cancelled()?;
+            self.emit(Event::RequestStarted { url: path, attempt });
+            let result = self.send_authenticated(path, query);
+            match self
+                .retry_policy
+                .retry_delay(attempt, started.elapsed(), &result)
+            {
+                Some(delay) => {
+                    match &result {
+                        Ok(response) => warn!(
+                            url = path, status = response.status, attempt, delay:? = delay;
+                            "request failed, retrying"
+                        ),
+                        Err(e) => warn!(
+                            url = path, attempt, delay:? = delay;
+                            "request failed, retrying: {e}"
+                        ),
+                    }
+                    self.emit(Event::RetryScheduled {
+                        url: path,
+                        attempt,
+                        status: result.as_ref().ok().map(|r| r.status),
+                        delay,
+                    });
+                    self.sleep(delay)?;
+                    attempt += 1;
                 }
-            };
+                None => return check_status(result?),
+            }
         }
-        Ok(mats)
     }
-}
 
-/// Fetch and retry if error code is 429 | 503
-fn get_response(path: &str, auth: &str, filter: &str) -> Result<Response, Error> {
-    for _ in 1..4 {
-        match ureq::get(path)
-            .set("Authorization", auth)
-            .query("mf", filter)
-            .call()
-        {
-            Err(ureq::Error::Status(503, r)) | Err(ureq::Error::Status(429, r)) => {
-                let retry: Option<u64> = r.header("retry-after").and_then(|h| h.parse().ok());
-                let retry = retry.unwrap_or(5);
-                eprintln!(
-                    "ERROR: {status} for {path}, retry in {retry}",
-                    status = r.status()
-                );
-                thread::sleep(Duration::from_secs(retry));
+    /// A copy of this client whose deadline counts from now, unless there is no deadline or
+    /// an operation is already running
+    fn start_operation(&self) -> Option<Ec3api> {
+        let timeout = self.deadline.filter(|_| self.deadline_at.is_none())?;
+        Some(self.with(|api| api.deadline_at = Some(Instant::now() + timeout)))
+    }
+
+    /// Error out if the cancel token was cancelled or the deadline has passed
+    fn check_cancelled(&self) -> Result<(), ApiError> {
+        if self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            return Err(ApiError::Cancelled);
+        }
+        if self.deadline_at.is_some_and(|d| Instant::now() >= d) {
+            return Err(ApiError::DeadlineExceeded);
+        }
+        Ok(())
+    }
+
+    /// Time left before the deadline, if any
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline_at
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// Sleep for `delay`, waking up early on cancellation or at the deadline
+    fn sleep(&self, delay: Duration) -> Result<(), ApiError> {
+        let delay = self.remaining().map_or(delay, |r| delay.min(r));
+        match &self.cancel_token {
+            Some(token) => {
+                token.sleep(delay);
             }
-            result => return result,
+            None => thread::sleep(delay),
+        }
+        self.check_cancelled()
+    }
+
+    /// Send a GET request with the stored token. Logs in first when no token is stored yet,
+    /// and once more if the api rejects the token when credentials are configured.
+    fn send_authenticated(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<HttpResponse, ApiError> {
+        if self.credentials.is_some() && self.api_key.lock().unwrap().is_empty() {
+            self.login()?;
+        }
+        let response = self.send(&self.get_request(path, query))?;
+        if response.status == 401 && self.credentials.is_some() {
+            info!(url = path; "token rejected, logging in again");
+            self.login()?;
+            return self.send(&self.get_request(path, query));
+        }
+        Ok(response)
+    }
+
+    /// Send a request through the transport, throttled by the rate limiter
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, ApiError> {
+        if self.cache_mode == CacheMode::OnlyIfCached {
+            return Err(ApiError::NotCached(request.url.clone()));
+        }
+        if let Some(limiter) = &self.rate_limiter {
+            while let Err(wait) = limiter.try_acquire() {
+                self.sleep(wait)?;
+            }
+        }
+        self.check_cancelled()?;
+        let started = Instant::now();
+        let response = self.transport.send(request)?;
+        debug!(
+            url = request.url.as_str(), status = response.status, duration:? = started.elapsed();
+            "response received"
+        );
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.observe(&response);
+        }
+        Ok(response)
+    }
+
+    fn get_request(&self, path: &str, query: &[(&str, String)]) -> HttpRequest {
+        let auth = format!("Bearer {}", self.api_key.lock().unwrap());
+        let request = query.iter().fold(
+            HttpRequest::get(path).header("Authorization", &auth),
+            |request, (param, value)| request.query(param, value),
+        );
+        match self.remaining() {
+            Some(remaining) => request.timeout(remaining),
+            None => request,
+        }
+    }
+}
+
+/// Map unsuccessful status codes to an [`ApiError`]
+fn check_status(response: HttpResponse) -> Result<HttpResponse, ApiError> {
+    match response.status {
+        _ if response.is_success() => Ok(response),
+        401 => Err(ApiError::AuthError),
+        429 => Err(ApiError::TooManyRequest(response.body)),
+        status => {
+            warn!(status; "request failed");
+            Err(ApiError::RequestError())
         }
     }
-    // Ran out of retries; try one last time and return whatever result we get.
-    ureq::get(path)
-        .set("Authorization", auth)
-        .query("mf", filter)
-        .call()
 }
 
-fn get_categories(json: Value) -> Result<Node<Ec3Category>, error::ApiError> {
+/// True when `page_number` is the last page according to the paging headers.
+/// Without paging headers there is no way to tell whether more pages exist.
+pub(crate) fn is_last_page(
+    page_number: usize,
+    received: usize,
+    total_pages: Option<usize>,
+    total_count: Option<usize>,
+) -> bool {
+    match (total_pages, total_count) {
+        (Some(pages), _) => page_number >= pages,
+        (None, Some(count)) => received >= count,
+        (None, None) => true,
+    }
+}
+
+pub(crate) fn get_categories(json: Value) -> Result<Node<Ec3Category>, error::ApiError> {
     let mut root = Node::new();
 
     parse_tree(&json, &mut root);
@@ -266,6 +1163,24 @@ fn get_categories(json: Value) -> Result<Node<Ec3Category>, error::ApiError> {
     Ok(root)
 }
 
+/// Parse a single category object and its subcategories
+pub(crate) fn get_category(json: &Value) -> Result<Node<Ec3Category>, error::ApiError> {
+    let name = json["name"]
+        .as_str()
+        .ok_or_else(|| ApiError::DeserializationError(de::Error::missing_field("name")))?;
+    let declared_unit = json["declared_unit"].as_str().unwrap_or_default();
+    let declared_unit = DeclaredUnit::from_str(declared_unit).unwrap_or_default();
+    let id = json["id"].as_str().unwrap_or_default().to_string();
+
+    let mut node = Node::with_category(name, declared_unit, id);
+    if let Some(subcategories) = json["subcategories"].as_array() {
+        for subcategory in subcategories {
+            node.add_children(get_category(subcategory)?);
+        }
+    }
+    Ok(node)
+}
+
 fn parse_tree(json: &Value, parent: &mut Node<Ec3Category>) {
     if let Value::Object(root) = json {
         let subcs = root.get("subcategories").unwrap();
@@ -284,35 +1199,19 @@ fn parse_tree(json: &Value, parent: &mut Node<Ec3Category>) {
     }
 }
 
-fn get_materials(json: Value) -> Result<Vec<Ec3Material>, error::ApiError> {
-    let mut materials: Vec<Ec3Material> = Vec::new();
-
-    json.as_array()
-        .ok_or(ApiError::EmptyArray())?
-        .iter()
-        .for_each(|v| {
-            let material: Ec3Material = serde_json::from_value(v.to_owned()).unwrap();
-
-            materials.push(material);
-        });
-    Ok(materials)
+pub(crate) fn get_list<T: DeserializeOwned>(json: Value) -> Result<Vec<T>, error::ApiError> {
+    match json {
+        Value::Array(_) => serde_json::from_value(json).map_err(ApiError::DeserializationError),
+        _ => Err(ApiError::EmptyArray()),
+    }
 }
 
 impl Debug for Ec3api {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Ec3api")
-            .field("api_key", &self.api_key)
-            .finish()
-    }
-}
-impl Display for Country {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match &self {
-            Country::US => write!(f, "US"),
-            Country::Germany => write!(f, "DE"),
-            Country::UK => write!(f, "UK"),
-            Country::None => write!(f, ""),
-        }
+            .field("credentials", &self.credentials)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
     }
 }
 
@@ -321,6 +1220,10 @@ impl Display for Endpoint {
         match *self {
             Endpoint::Materials => write!(f, "materials"),
             Endpoint::Categories => write!(f, "categories/root"),
+            Endpoint::Epds => write!(f, "epds"),
+            Endpoint::Plants => write!(f, "plants"),
+            Endpoint::Orgs => write!(f, "orgs"),
+            Endpoint::Projects => write!(f, "projects"),
         }
     }
 }
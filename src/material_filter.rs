@@ -3,13 +3,13 @@ extern crate serde_json;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 struct Pragma {
     name: String,
     args: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 struct Filter {
     field: String,
     op: String,
@@ -26,7 +26,7 @@ impl Filter {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MaterialFilter {
     pragma: Vec<Pragma>,
     category: String,
@@ -100,7 +100,6 @@ pub fn convert(mf: &MaterialFilter) -> String {
         }
     }
 
-    // TODO removeme
-    println!("{}", &response);
+    log::trace!(filter = response.as_str(); "converted material filter");
     response
 }
@@ -1,8 +1,14 @@
-use crate::error::ApiError;
+use crate::{error::ApiError, jurisdiction::Jurisdiction};
+use log::warn;
 use serde::{de, Deserialize, Deserializer, Serialize};
-use std::{hash::Hash, str::FromStr};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    str::FromStr,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ec3Category {
     pub name: String,
     pub declared_unit: DeclaredUnit,
@@ -20,7 +26,7 @@ impl Default for Ec3Category {
         }
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node<T> {
     pub children: Option<Vec<Self>>,
     pub value: T,
@@ -71,7 +77,190 @@ pub struct Ec3Material {
     pub id: String,
     #[serde(deserialize_with = "deserialize_from_str")]
     pub declared_unit: DeclaredUnit,
+    #[serde(default)]
+    pub open_xpd_uuid: Option<String>,
+    /// Plant, or group of plants, the material was produced at
+    #[serde(default)]
+    pub plant_or_group: Option<Plant>,
+    /// Fields of the api response not modelled above
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Material along with the jurisdiction whose query returned it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaggedMaterial {
+    pub jurisdiction: Jurisdiction,
+    pub material: Ec3Material,
+}
+
+/// Manufacturing plant
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Plant {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub owned_by: Option<OrgRef>,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    /// Jurisdiction the plant is located in. Subdivisions missing from the jurisdiction tables
+    /// fall back to their country, other unknown codes are `None`
+    #[serde(default, deserialize_with = "deserialize_plant_jurisdiction")]
+    pub jurisdiction: Option<Jurisdiction>,
+}
+/// Organization, e.g. a manufacturer publishing EPDs
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Org {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub website: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub plants: Vec<Plant>,
+    /// Number of EPDs published by the organization
+    #[serde(default)]
+    pub epds_count: Option<u64>,
+}
+/// Reference to an organization
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgRef {
+    pub name: String,
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+/// EC3 project, grouping buildings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Building {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Gross floor area in m2
+    #[serde(default)]
+    pub gross_floor_area: Option<f64>,
+}
+/// Quantity of a material assigned to a building
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MaterialQuantity {
+    pub material: Ec3Material,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub quantity: DeclaredUnit,
+}
+/// Materials assigned to a building
+#[derive(Clone, Debug)]
+pub struct BillOfMaterials {
+    pub building: Building,
+    pub items: Vec<MaterialQuantity>,
 }
+
+/// Global warming potential statistics of the materials in a category, per the category's
+/// declared unit. EC3 uses `conservative_estimate` as baseline and `pct20_gwp` as achievable
+/// target.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub id: String,
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub declared_unit: DeclaredUnit,
+    #[serde(default)]
+    pub epds_count: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub mean_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct10_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct20_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct30_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct40_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct50_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct60_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct70_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct80_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub pct90_gwp: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub conservative_estimate: Option<Gwp>,
+    #[serde(default, deserialize_with = "deserialize_option_from_str")]
+    pub lowest_plausible_gwp: Option<Gwp>,
+}
+
+/// Environmental Product Declaration document underlying materials
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Epd {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub open_xpd_uuid: Option<String>,
+    #[serde(default)]
+    pub program_operator: Option<ProgramOperator>,
+    #[serde(default)]
+    pub verifier: Option<Verifier>,
+    /// Issue date, formatted as YYYY-MM-DD
+    #[serde(default)]
+    pub date_of_issue: Option<String>,
+    /// Expiry date, formatted as YYYY-MM-DD
+    #[serde(default)]
+    pub date_validity_ends: Option<String>,
+    #[serde(default)]
+    pub pcr: Option<Pcr>,
+    #[serde(deserialize_with = "deserialize_from_str")]
+    pub declared_unit: DeclaredUnit,
+    /// Impact results by LCIA method, then indicator, then life cycle stage
+    #[serde(default)]
+    pub impacts: BTreeMap<String, BTreeMap<String, BTreeMap<String, Impact>>>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProgramOperator {
+    pub name: String,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub website: Option<String>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Verifier {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+/// Product Category Rules the EPD was made under
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pcr {
+    pub name: String,
+    #[serde(default)]
+    pub id: Option<String>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Impact {
+    #[serde(default)]
+    pub mean: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Manufacturer {
     pub name: String,
@@ -166,6 +355,40 @@ where
     S::from_str(&s).map_err(de::Error::custom)
 }
 
+fn deserialize_plant_jurisdiction<'de, D>(deserializer: D) -> Result<Option<Jurisdiction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    let Some(s) = s.filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    let jurisdiction = s.parse().or_else(|e| match s.split_once('-') {
+        Some((country, _)) => Jurisdiction::country(country),
+        None => Err(e),
+    });
+    // A single plant in an unlisted jurisdiction shouldn't fail a whole page of materials
+    match jurisdiction {
+        Ok(jurisdiction) => Ok(Some(jurisdiction)),
+        Err(e) => {
+            warn!(jurisdiction = s.as_str(); "ignoring plant jurisdiction: {e}");
+            Ok(None)
+        }
+    }
+}
+
+// Same as deserialize_from_str, for optional and nullable fields
+fn deserialize_option_from_str<'de, S, D>(deserializer: D) -> Result<Option<S>, D::Error>
+where
+    S: FromStr,
+    S::Err: std::fmt::Display,
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    s.map(|s| S::from_str(&s).map_err(de::Error::custom))
+        .transpose()
+}
+
 impl Gwp {
     pub fn as_str(&self) -> String {
         format!("{} {:?}", self.value, self.unit)
@@ -202,6 +425,138 @@ impl FromStr for DeclaredUnit {
         Ok(DeclaredUnit { value, unit })
     }
 }
+impl Epd {
+    /// Mean of an impact indicator for a life cycle stage, e.g. ("EF 3.0", "gwp", "A1A2A3")
+    pub fn impact(&self, method: &str, indicator: &str, stage: &str) -> Option<&Impact> {
+        self.impacts.get(method)?.get(indicator)?.get(stage)
+    }
+    /// True if the EPD is no longer valid on `date`, formatted as YYYY-MM-DD
+    pub fn is_expired_on(&self, date: &str) -> bool {
+        self.date_validity_ends
+            .as_deref()
+            .is_some_and(|end| end < date)
+    }
+}
+
+impl MaterialQuantity {
+    /// Embodied carbon of the quantity in kgCO2e, `None` if the quantity's unit differs from the
+    /// declared unit of the material
+    pub fn gwp(&self) -> Option<f64> {
+        let declared = &self.material.declared_unit;
+        if self.quantity.unit != declared.unit || declared.value == 0. {
+            return None;
+        }
+        Some(self.quantity.value / declared.value * self.material.gwp.value)
+    }
+}
+
+impl BillOfMaterials {
+    /// Total embodied carbon in kgCO2e, skipping quantities whose unit cannot be converted
+    pub fn total_gwp(&self) -> f64 {
+        self.items.iter().filter_map(MaterialQuantity::gwp).sum()
+    }
+    /// Embodied carbon in kgCO2e per material category
+    pub fn gwp_by_category(&self) -> HashMap<Category, f64> {
+        let mut breakdown = HashMap::new();
+        for item in &self.items {
+            if let Some(gwp) = item.gwp() {
+                *breakdown.entry(item.material.category.clone()).or_default() += gwp;
+            }
+        }
+        breakdown
+    }
+}
+
+impl CategoryStats {
+    /// Known percentiles as (percentile, kgCO2e per declared unit), in increasing order
+    pub fn percentiles(&self) -> Vec<(f64, f64)> {
+        [
+            (10., &self.pct10_gwp),
+            (20., &self.pct20_gwp),
+            (30., &self.pct30_gwp),
+            (40., &self.pct40_gwp),
+            (50., &self.pct50_gwp),
+            (60., &self.pct60_gwp),
+            (70., &self.pct70_gwp),
+            (80., &self.pct80_gwp),
+            (90., &self.pct90_gwp),
+        ]
+        .into_iter()
+        .filter_map(|(pct, gwp)| gwp.as_ref().map(|gwp| (pct, gwp.value)))
+        .collect()
+    }
+
+    /// GWP of a material scaled to the declared unit of the category,
+    /// `None` if the units differ
+    pub fn normalized_gwp(&self, material: &Ec3Material) -> Option<f64> {
+        if material.declared_unit.unit != self.declared_unit.unit
+            || material.declared_unit.value == 0.
+        {
+            return None;
+        }
+        Some(material.gwp.value / material.declared_unit.value * self.declared_unit.value)
+    }
+
+    /// Percentile of the category a material sits at, interpolated between the known
+    /// percentiles and clamped to the lowest and highest of them
+    pub fn percentile_of(&self, material: &Ec3Material) -> Option<f64> {
+        let gwp = self.normalized_gwp(material)?;
+        let percentiles = self.percentiles();
+        let (first, last) = (percentiles.first()?, percentiles.last()?);
+        if gwp <= first.1 {
+            return Some(first.0);
+        }
+        if gwp >= last.1 {
+            return Some(last.0);
+        }
+        percentiles.windows(2).find_map(|w| {
+            let ((p0, v0), (p1, v1)) = (w[0], w[1]);
+            (gwp >= v0 && gwp <= v1).then(|| {
+                if v1 == v0 {
+                    p0
+                } else {
+                    p0 + (gwp - v0) / (v1 - v0) * (p1 - p0)
+                }
+            })
+        })
+    }
+
+    /// True if the material is below the conservative baseline of the category
+    pub fn is_below_baseline(&self, material: &Ec3Material) -> Option<bool> {
+        let baseline = self.conservative_estimate.as_ref()?.value;
+        Some(self.normalized_gwp(material)? < baseline)
+    }
+
+    /// True if the material meets the achievable target (20th percentile) of the category
+    pub fn is_achievable(&self, material: &Ec3Material) -> Option<bool> {
+        let target = self.pct20_gwp.as_ref()?.value;
+        Some(self.normalized_gwp(material)? <= target)
+    }
+}
+
+impl Plant {
+    /// Great-circle distance in kilometers to a point, if the plant is geolocated
+    pub fn distance_km(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        const EARTH_RADIUS_KM: f64 = 6371.0;
+        let (lat1, lon1) = (self.latitude?.to_radians(), self.longitude?.to_radians());
+        let (lat2, lon2) = (latitude.to_radians(), longitude.to_radians());
+
+        let a = ((lat2 - lat1) / 2.).sin().powi(2)
+            + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.).sin().powi(2);
+        Some(2. * EARTH_RADIUS_KM * a.sqrt().asin())
+    }
+}
+
+impl Org {
+    /// The manufacturer as embedded in materials published by the organization
+    pub fn manufacturer(&self) -> Manufacturer {
+        Manufacturer {
+            name: self.name.clone(),
+            country: self.country.clone(),
+        }
+    }
+}
+
 impl Hash for Category {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.id.hash(state);
@@ -225,3 +580,14 @@ impl PartialEq for Manufacturer {
     }
 }
 impl Eq for Manufacturer {}
+impl Hash for Org {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+impl PartialEq for Org {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl Eq for Org {}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+/// Something happening while the client fetches, reported to an [`Observer`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// A request is about to be sent. `attempt` starts at 1 and grows with each retry
+    RequestStarted {
+        url: &'a str,
+        attempt: u32,
+    },
+    /// A page of a list endpoint was received
+    PageReceived {
+        url: &'a str,
+        page_number: Option<usize>,
+        records: usize,
+        total_pages: Option<usize>,
+        total_count: Option<usize>,
+    },
+    /// A failed request will be sent again after `delay`. `status` is `None` for transport errors
+    RetryScheduled {
+        url: &'a str,
+        attempt: u32,
+        status: Option<u16>,
+        delay: Duration,
+    },
+    CacheHit {
+        cache_key: &'a str,
+    },
+    CacheMiss {
+        cache_key: &'a str,
+    },
+    /// Every requested page of a list endpoint was fetched
+    Finished {
+        url: &'a str,
+        records: usize,
+        elapsed: Duration,
+    },
+}
+
+/// Receives the [`Event`]s of a client, e.g. to draw progress bars or export metrics.
+///
+/// Events are delivered synchronously on the thread doing the request, so observers should
+/// return quickly. Closures taking an `&Event` are observers.
+pub trait Observer: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F> Observer for F
+where
+    F: Fn(&Event) + Send + Sync,
+{
+    fn on_event(&self, event: &Event) {
+        self(event)
+    }
+}
@@ -0,0 +1,286 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    error::ApiError,
+    get_categories, get_category, get_list,
+    jurisdiction::Jurisdiction,
+    material_filter::{convert, MaterialFilter},
+    models::{
+        Building, CategoryStats, Ec3Category, Ec3Material, Epd, MaterialQuantity, Node, Org, Plant,
+        Project,
+    },
+    Ec3api, Endpoint,
+};
+
+/// A request to an endpoint of the api, declaring the type of its response.
+///
+/// Run with [`Ec3api::execute`]. By default the query is sent as a single request and its json
+/// body handed to [`Query::parse`]; queries of list endpoints override [`Query::execute`] to walk
+/// pages with [`Ec3api::fetch_list`].
+pub trait Query {
+    type Output;
+
+    /// Path of the endpoint, relative to the base url
+    fn path(&self) -> String;
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError>;
+
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        let json = api.get_json(&self.path(), &self.params())?;
+        self.parse(json)
+    }
+}
+
+/// Deserialize a json response into `T`
+fn parse_as<T: DeserializeOwned>(json: Value) -> Result<T, ApiError> {
+    serde_json::from_value(json).map_err(ApiError::DeserializationError)
+}
+
+/// Search materials, walking pages as configured on the client
+#[derive(Debug, Clone, Default)]
+pub struct MaterialsQuery {
+    pub filter: Option<MaterialFilter>,
+    pub jurisdiction: Option<Jurisdiction>,
+}
+
+impl Query for MaterialsQuery {
+    type Output = Vec<Ec3Material>;
+
+    fn path(&self) -> String {
+        Endpoint::Materials.to_string()
+    }
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let filter = self.filter.as_ref().map(convert).unwrap_or_default();
+        let mut params = Vec::new();
+        if let Some(jurisdiction) = &self.jurisdiction {
+            params.push(("jurisdiction", jurisdiction.code()));
+        }
+        params.push(("mf", filter));
+        params
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_list(json)
+    }
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        api.fetch_configured_list(&self.path(), &self.params())
+    }
+}
+
+/// A single material
+#[derive(Debug, Clone)]
+pub struct MaterialQuery {
+    pub id: String,
+}
+
+impl Query for MaterialQuery {
+    type Output = Ec3Material;
+
+    fn path(&self) -> String {
+        format!("{}/{}", Endpoint::Materials, self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+/// The whole category tree
+#[derive(Debug, Clone, Default)]
+pub struct CategoriesQuery;
+
+impl Query for CategoriesQuery {
+    type Output = Node<Ec3Category>;
+
+    fn path(&self) -> String {
+        Endpoint::Categories.to_string()
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_categories(json)
+    }
+}
+
+/// A single category and its subcategories
+#[derive(Debug, Clone)]
+pub struct CategoryQuery {
+    pub id: String,
+}
+
+impl Query for CategoryQuery {
+    type Output = Node<Ec3Category>;
+
+    fn path(&self) -> String {
+        format!("categories/{}", self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_category(&json)
+    }
+}
+
+/// GWP statistics and baselines of a category
+#[derive(Debug, Clone)]
+pub struct CategoryStatsQuery {
+    pub id: String,
+}
+
+impl Query for CategoryStatsQuery {
+    type Output = CategoryStats;
+
+    fn path(&self) -> String {
+        format!("categories/{}", self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EpdQuery {
+    pub id: String,
+}
+
+impl Query for EpdQuery {
+    type Output = Epd;
+
+    fn path(&self) -> String {
+        format!("{}/{}", Endpoint::Epds, self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlantQuery {
+    pub id: String,
+}
+
+impl Query for PlantQuery {
+    type Output = Plant;
+
+    fn path(&self) -> String {
+        format!("{}/{}", Endpoint::Plants, self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OrgQuery {
+    pub id: String,
+}
+
+impl Query for OrgQuery {
+    type Output = Org;
+
+    fn path(&self) -> String {
+        format!("{}/{}", Endpoint::Orgs, self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+/// Every organization, or those whose name contains `name`. Walks all pages
+#[derive(Debug, Clone, Default)]
+pub struct OrgsQuery {
+    pub name: Option<String>,
+}
+
+impl Query for OrgsQuery {
+    type Output = Vec<Org>;
+
+    fn path(&self) -> String {
+        Endpoint::Orgs.to_string()
+    }
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match &self.name {
+            Some(name) => vec![("name__like", name.clone())],
+            None => Vec::new(),
+        }
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_list(json)
+    }
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        api.fetch_list(&self.path(), &self.params(), true)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectQuery {
+    pub id: String,
+}
+
+impl Query for ProjectQuery {
+    type Output = Project;
+
+    fn path(&self) -> String {
+        format!("{}/{}", Endpoint::Projects, self.id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        parse_as(json)
+    }
+}
+
+/// Every project of the account. Walks all pages
+#[derive(Debug, Clone, Default)]
+pub struct ProjectsQuery;
+
+impl Query for ProjectsQuery {
+    type Output = Vec<Project>;
+
+    fn path(&self) -> String {
+        Endpoint::Projects.to_string()
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_list(json)
+    }
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        api.fetch_list(&self.path(), &self.params(), true)
+    }
+}
+
+/// Buildings of a project. Walks all pages
+#[derive(Debug, Clone)]
+pub struct BuildingsQuery {
+    pub project_id: String,
+}
+
+impl Query for BuildingsQuery {
+    type Output = Vec<Building>;
+
+    fn path(&self) -> String {
+        format!("{}/{}/buildings", Endpoint::Projects, self.project_id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_list(json)
+    }
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        api.fetch_list(&self.path(), &self.params(), true)
+    }
+}
+
+/// Material quantities assigned to a building. Walks all pages
+#[derive(Debug, Clone)]
+pub struct BuildingMaterialsQuery {
+    pub building_id: String,
+}
+
+impl Query for BuildingMaterialsQuery {
+    type Output = Vec<MaterialQuantity>;
+
+    fn path(&self) -> String {
+        format!("buildings/{}/materials", self.building_id)
+    }
+    fn parse(&self, json: Value) -> Result<Self::Output, ApiError> {
+        get_list(json)
+    }
+    fn execute(&self, api: &Ec3api) -> Result<Self::Output, ApiError> {
+        api.fetch_list(&self.path(), &self.params(), true)
+    }
+}
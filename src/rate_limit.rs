@@ -0,0 +1,127 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::transport::HttpResponse;
+
+/// `x-ratelimit-reset` values from this one on are unix timestamps, around 2001 and later
+const EPOCH_RESET_SECS: u64 = 1_000_000_000;
+/// Longest pause asked by a response that is obeyed, so a bogus header can't stall every client
+const MAX_PAUSE_SECS: u64 = 3600;
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Token bucket throttling requests before they reach the api.
+///
+/// Share one limiter between clients, and threads, by wrapping it in an `Arc` and handing it to
+/// [`crate::Ec3api::rate_limiter`]. The limiter also pauses when responses carry a `retry-after`
+/// header or report an exhausted `x-ratelimit-remaining`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Allow `requests_per_second` on average, with bursts of up to `burst` requests
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        RateLimiter {
+            capacity,
+            refill_per_sec: requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                blocked_until: None,
+            }),
+        }
+    }
+
+    /// Allow `requests` per minute, with bursts of up to `burst` requests
+    pub fn per_minute(requests: u32, burst: u32) -> Self {
+        Self::new(f64::from(requests) / 60., burst)
+    }
+
+    /// Take a token if one is available, otherwise return how long to wait before trying again
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+
+        if let Some(until) = bucket.blocked_until {
+            if until > now {
+                return Err(until - now);
+            }
+            bucket.blocked_until = None;
+        }
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1. {
+            bucket.tokens -= 1.;
+            Ok(())
+        } else if self.refill_per_sec > 0. {
+            Err(Duration::from_secs_f64(
+                (1. - bucket.tokens) / self.refill_per_sec,
+            ))
+        } else {
+            Err(Duration::from_secs(1))
+        }
+    }
+
+    /// Block until a token is available
+    pub fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Learn from the rate limit headers of a response
+    pub fn observe(&self, response: &HttpResponse) {
+        let header_secs = |name: &str| -> Option<u64> {
+            response.header(name).and_then(|h| h.trim().parse().ok())
+        };
+        let pause = if matches!(response.status, 429 | 503) {
+            header_secs("retry-after")
+        } else {
+            None
+        };
+        let pause = pause.or_else(|| match header_secs("x-ratelimit-remaining") {
+            // Sent by some apis as the time of the reset rather than the seconds until it
+            Some(0) => header_secs("x-ratelimit-reset")
+                .map(|reset| {
+                    if reset >= EPOCH_RESET_SECS {
+                        reset.saturating_sub(unix_secs())
+                    } else {
+                        reset
+                    }
+                })
+                .filter(|secs| *secs > 0),
+            _ => None,
+        });
+
+        let mut bucket = self.bucket.lock().unwrap();
+        if let Some(secs) = pause {
+            let until = Instant::now() + Duration::from_secs(secs.min(MAX_PAUSE_SECS));
+            bucket.blocked_until = Some(bucket.blocked_until.map_or(until, |b| b.max(until)));
+            bucket.tokens = 0.;
+        } else if response.status == 429 {
+            bucket.tokens = 0.;
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::{error::ApiError, transport::HttpResponse};
+
+/// When and how long to wait before retrying a failed request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every following retry
+    pub base_delay: Duration,
+    /// Upper bound of the computed delay
+    pub max_delay: Duration,
+    /// Randomize each delay between half and all of its computed value
+    pub jitter: bool,
+    /// Status codes that are retried
+    pub retry_statuses: Vec<u16>,
+    /// Retry when no response could be obtained, e.g. connection resets
+    pub retry_transport_errors: bool,
+    /// Wait as long as the `retry-after` header asks instead of the computed delay
+    pub respect_retry_after: bool,
+    /// Give up once retrying would exceed this much time since the first attempt
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            jitter: true,
+            retry_statuses: vec![429, 502, 503, 504],
+            retry_transport_errors: true,
+            respect_retry_after: true,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay to wait before the next attempt, or `None` to stop and return `result`.
+    /// `attempt` is the number of the attempt that produced `result`, starting at 1.
+    pub fn retry_delay(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+        result: &Result<HttpResponse, ApiError>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let retry_after = match result {
+            Ok(response) if self.retry_statuses.contains(&response.status) => response
+                .header("retry-after")
+                .and_then(|h| h.trim().parse().ok())
+                .map(Duration::from_secs),
+            Err(ApiError::TransportError(_)) if self.retry_transport_errors => None,
+            _ => return None,
+        };
+
+        let delay = match retry_after {
+            Some(retry_after) if self.respect_retry_after => retry_after,
+            _ => self.backoff(attempt),
+        };
+        match self.deadline {
+            Some(deadline) if elapsed + delay > deadline => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Exponential delay after `attempt` failed attempts, capped and jittered
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self
+            .base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        if self.jitter {
+            delay.mul_f64(0.5 + random_fraction() / 2.)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Random number in [0, 1), good enough for spreading retries
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
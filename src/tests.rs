@@ -1,9 +1,21 @@
+use crate::cancel::CancelToken;
 use crate::error;
+use crate::jurisdiction::{Jurisdiction, JurisdictionKind};
 use crate::material_filter::convert;
 use crate::material_filter::MaterialFilter;
+use crate::models::{Ec3Category, Ec3Material, Node};
+use crate::observer::Event;
+use crate::query::{CategoryQuery, MaterialsQuery};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::transport::{HttpResponse, MockTransport};
+use crate::utils::CacheMode;
 use crate::Ec3Result;
 use crate::{Ec3api, Endpoint};
 use dotenv::dotenv;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 type Result<T> = std::result::Result<T, error::ApiError>;
 
@@ -21,7 +33,7 @@ fn fetch_from_category() -> Result<()> {
         .material_filter(mf)
         .fetch()?;
     println!("{:?}", materials.first());
-    assert!(materials.len() > 0, "Fetch returned no results");
+    assert!(!materials.is_empty(), "Fetch returned no results");
     Ok(())
 }
 
@@ -59,3 +71,1452 @@ fn test_material_filter() {
 
     assert_eq!(convert(&mf).as_str(), converted)
 }
+
+fn material_json(id: &str) -> String {
+    format!(
+        r#"{{
+            "name": "Material {id}",
+            "gwp": "250.5 kgCO2e",
+            "manufacturer": {{"name": "Acme", "country": "DE"}},
+            "description": "",
+            "category": {{"description": "", "name": "Concrete", "display_name": "Concrete", "id": "c1"}},
+            "id": "{id}",
+            "declared_unit": "1 m3"
+        }}"#
+    )
+}
+
+fn materials_page(ids: &[&str], total_pages: usize) -> HttpResponse {
+    let materials: Vec<String> = ids.iter().map(|id| material_json(id)).collect();
+    HttpResponse::ok(&format!("[{}]", materials.join(",")))
+        .with_header("X-Total-Pages", &total_pages.to_string())
+}
+
+#[test]
+fn fetch_walks_all_pages() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 2))
+        .with_response("materials", materials_page(&["c"], 2))
+        .shared();
+
+    let materials = Ec3api::new("key")
+        .transport(transport.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"))
+        .page_size(2)
+        .all_pages(true)
+        .fetch()?;
+
+    let ids: Vec<&str> = materials.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].query_param("page_number"), Some("2"));
+    assert_eq!(requests[1].query_param("page_size"), Some("2"));
+    Ok(())
+}
+
+#[test]
+fn last_page_from_headers() {
+    assert!(!crate::is_last_page(1, 2, Some(2), None));
+    assert!(crate::is_last_page(2, 4, Some(2), None));
+    assert!(
+        crate::is_last_page(2, 4, Some(2), Some(100)),
+        "Total pages should win over the total count"
+    );
+    assert!(!crate::is_last_page(1, 2, None, Some(3)));
+    assert!(crate::is_last_page(2, 3, None, Some(3)));
+    assert!(
+        crate::is_last_page(1, 2, None, None),
+        "Pages without headers should be the last"
+    );
+}
+
+#[test]
+fn max_records_truncates_pages() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 3))
+        .with_response("materials", materials_page(&["c", "d"], 3))
+        .with_response("materials", materials_page(&["e", "f"], 3))
+        .shared();
+
+    let materials = Ec3api::new("key")
+        .transport(transport.clone())
+        .use_cache(false)
+        .page_size(2)
+        .all_pages(true)
+        .max_records(3)
+        .fetch()?;
+
+    let ids: Vec<&str> = materials.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+    assert_eq!(
+        transport.requests().len(),
+        2,
+        "Paging should stop once enough records are fetched"
+    );
+    Ok(())
+}
+
+#[test]
+fn iter_materials_stops_early() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 3))
+        .with_response("materials", materials_page(&["c", "d"], 3))
+        .shared();
+
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone());
+
+    let first: Vec<String> = api
+        .iter_materials()
+        .take(3)
+        .map(|m| m.unwrap().id)
+        .collect();
+
+    assert_eq!(first, vec!["a", "b", "c"]);
+    assert_eq!(
+        transport.requests().len(),
+        2,
+        "Third page should not be fetched"
+    );
+}
+
+#[test]
+fn iter_materials_respects_paging_settings() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["c", "d"], 3))
+        .with_response("materials", materials_page(&["e", "f"], 3))
+        .shared();
+
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .page_number(2)
+        .max_records(3);
+
+    let ids: Vec<String> = api.iter_materials().map(|m| m.unwrap().id).collect();
+
+    assert_eq!(ids, vec!["c", "d", "e"]);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2, "Iteration should stop after max_records");
+    assert_eq!(requests[0].query_param("page_number"), Some("2"));
+}
+
+#[test]
+fn iter_materials_ends_after_error() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 2))
+        .with_response("materials", HttpResponse::new(400, "bad request"))
+        .shared();
+
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone());
+    let mut iter = api.iter_materials();
+
+    assert_eq!(iter.next().unwrap().unwrap().id, "a");
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn unauthorized_maps_to_auth_error() {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(401, ""))
+        .shared();
+
+    let result = Ec3api::new("bad key").transport(transport).fetch();
+
+    assert!(matches!(result, Err(error::ApiError::AuthError)));
+}
+
+#[test]
+fn base_url_is_respected() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+
+    Ec3api::new("key")
+        .transport(transport.clone())
+        .base_url("http://localhost:8080/api")
+        .fetch()?;
+
+    assert_eq!(
+        transport.requests()[0].url,
+        "http://localhost:8080/api/materials"
+    );
+    Ok(())
+}
+
+#[test]
+fn login_and_refresh_expired_token() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("rest-auth/login", HttpResponse::ok(r#"{"key": "first"}"#))
+        .with_response("rest-auth/login", HttpResponse::ok(r#"{"key": "second"}"#))
+        .with_response("materials", HttpResponse::new(401, ""))
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+
+    let materials = Ec3api::with_credentials("user", "secret")
+        .transport(transport.clone())
+        .fetch()?;
+
+    assert_eq!(materials.len(), 1);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 4, "login, rejected fetch, login, fetch");
+    assert_eq!(
+        requests[1].header_value("Authorization"),
+        Some("Bearer first")
+    );
+    assert_eq!(
+        requests[3].header_value("Authorization"),
+        Some("Bearer second")
+    );
+    Ok(())
+}
+
+#[test]
+fn login_rejected_credentials() {
+    let transport = MockTransport::new()
+        .with_response("rest-auth/login", HttpResponse::new(400, ""))
+        .shared();
+
+    let result = Ec3api::with_credentials("user", "wrong")
+        .transport(transport)
+        .fetch();
+
+    assert!(matches!(result, Err(error::ApiError::AuthError)));
+}
+
+#[test]
+fn debug_hides_secrets() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response(
+            "rest-auth/login",
+            HttpResponse::ok(r#"{"key": "token123"}"#),
+        )
+        .shared();
+    let mut api = Ec3api::with_credentials("user", "secret");
+    api.transport(transport);
+    api.login()?;
+
+    let debug = format!("{api:?}");
+    assert!(debug.contains("user"));
+    assert!(!debug.contains("secret") && !debug.contains("token123"));
+    assert!(!format!("{:?}", Ec3api::new("apikey456")).contains("apikey456"));
+    Ok(())
+}
+
+fn no_delay_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::ZERO,
+        respect_retry_after: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn retries_retryable_statuses() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(503, ""))
+        .with_response("materials", HttpResponse::new(429, "slow down"))
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+
+    let materials = Ec3api::new("key")
+        .transport(transport.clone())
+        .retry_policy(no_delay_retries(3))
+        .fetch()?;
+
+    assert_eq!(materials.len(), 1);
+    assert_eq!(transport.requests().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn retry_policy_limits() {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(429, "slow down"))
+        .shared();
+
+    let result = Ec3api::new("key")
+        .transport(transport.clone())
+        .retry_policy(no_delay_retries(2))
+        .fetch();
+    assert!(matches!(result, Err(error::ApiError::TooManyRequest(_))));
+    assert_eq!(transport.requests().len(), 2);
+
+    let policy = RetryPolicy {
+        deadline: Some(Duration::from_secs(1)),
+        jitter: false,
+        ..Default::default()
+    };
+    let throttled = Ok(HttpResponse::new(503, "").with_header("Retry-After", "30"));
+    assert_eq!(policy.retry_delay(1, Duration::ZERO, &throttled), None);
+
+    let failed = Ok(HttpResponse::new(500, ""));
+    assert_eq!(policy.retry_delay(1, Duration::ZERO, &failed), None);
+
+    let unavailable = Ok(HttpResponse::new(503, ""));
+    assert_eq!(
+        policy.retry_delay(1, Duration::ZERO, &unavailable),
+        Some(Duration::from_secs(1))
+    );
+}
+
+#[test]
+fn rate_limiter_shared_between_clients() -> Result<()> {
+    let limiter = Arc::new(RateLimiter::new(0.001, 2));
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+
+    Ec3api::new("key")
+        .transport(transport.clone())
+        .rate_limiter(limiter.clone())
+        .fetch()?;
+    Ec3api::new("other key")
+        .transport(transport)
+        .rate_limiter(limiter.clone())
+        .fetch()?;
+
+    assert!(limiter.try_acquire().is_err(), "Burst should be used up");
+    Ok(())
+}
+
+#[test]
+fn timeouts_keep_custom_transport() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .timeouts(Duration::from_secs(1), Duration::from_secs(1));
+
+    assert_eq!(api.fetch()?.len(), 1);
+    assert_eq!(transport.requests().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn rate_limiter_wait_respects_cancellation() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+    let limiter = Arc::new(RateLimiter::new(0.001, 1));
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .rate_limiter(limiter);
+    api.fetch().unwrap();
+
+    let started = std::time::Instant::now();
+    let expiring = api.with(|req| {
+        req.deadline(Duration::from_millis(50));
+    });
+    assert!(matches!(
+        expiring.fetch(),
+        Err(error::ApiError::DeadlineExceeded)
+    ));
+
+    let token = CancelToken::new();
+    let cancelled = api.with(|req| {
+        req.cancel_token(token.clone());
+    });
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    });
+    assert!(matches!(cancelled.fetch(), Err(error::ApiError::Cancelled)));
+    canceller.join().unwrap();
+
+    assert!(
+        started.elapsed() < Duration::from_secs(10),
+        "Waiting for the limiter should be cut short"
+    );
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[test]
+fn rate_limiter_learns_from_headers() {
+    let limiter = RateLimiter::new(100., 10);
+    limiter.observe(&HttpResponse::ok("").with_header("X-RateLimit-Remaining", "5"));
+    assert!(limiter.try_acquire().is_ok());
+
+    limiter.observe(&HttpResponse::new(429, "").with_header("Retry-After", "30"));
+    let wait = limiter.try_acquire().expect_err("Limiter should pause");
+    assert!(wait > Duration::from_secs(29));
+}
+
+#[test]
+fn rate_limit_reset_as_timestamp() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let exhausted = |reset: u64| {
+        HttpResponse::ok("")
+            .with_header("X-RateLimit-Remaining", "0")
+            .with_header("X-RateLimit-Reset", &reset.to_string())
+    };
+
+    let limiter = RateLimiter::new(100., 10);
+    limiter.observe(&exhausted(now + 20));
+    let wait = limiter.try_acquire().expect_err("Limiter should pause");
+    assert!(wait > Duration::from_secs(18) && wait <= Duration::from_secs(20));
+
+    let limiter = RateLimiter::new(100., 10);
+    limiter.observe(&exhausted(now - 5));
+    assert!(
+        limiter.try_acquire().is_ok(),
+        "A past reset should not pause"
+    );
+
+    let limiter = RateLimiter::new(100., 10);
+    limiter.observe(&exhausted(900_000_000));
+    let wait = limiter.try_acquire().expect_err("Limiter should pause");
+    assert!(wait <= Duration::from_secs(3600), "Pauses should be capped");
+}
+
+#[test]
+fn epd_of_material() -> Result<()> {
+    let epd = r#"{
+        "id": "ec3epd1",
+        "open_xpd_uuid": "ec3epd1",
+        "name": "Ready mix",
+        "program_operator": {"name": "IBU", "website": "https://ibu-epd.com"},
+        "verifier": {"name": "Jane Doe"},
+        "date_of_issue": "2021-03-01",
+        "date_validity_ends": "2026-03-01",
+        "pcr": {"name": "PCR Concrete"},
+        "declared_unit": "1 m3",
+        "impacts": {"EF 3.0": {"gwp": {"A1A2A3": {"mean": 250.5, "unit": "kgCO2e"}}}}
+    }"#;
+    let material =
+        material_json("a").replace(r#""id": "a","#, r#""id": "a", "open_xpd_uuid": "ec3epd1","#);
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::ok(&format!("[{material}]")))
+        .with_response("epds/ec3epd1", HttpResponse::ok(epd))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone());
+
+    let mut material = api.fetch()?.remove(0);
+    let epd = api.epd_of(&material)?.expect("Expected the material's EPD");
+
+    assert_eq!(epd.id, "ec3epd1");
+    assert!(transport.requests()[1].url.ends_with("epds/ec3epd1"));
+
+    assert_eq!(epd.program_operator.as_ref().unwrap().name, "IBU");
+    assert_eq!(
+        epd.impact("EF 3.0", "gwp", "A1A2A3").unwrap().mean,
+        Some(250.5)
+    );
+    assert!(epd.is_expired_on("2026-10-17"));
+    assert!(!epd.is_expired_on("2025-01-01"));
+
+    material.open_xpd_uuid = None;
+    assert!(api.epd_of(&material)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn plants_with_distance() -> Result<()> {
+    let plant = r#"{
+        "id": "p1",
+        "name": "Munich plant",
+        "owned_by": {"name": "Acme", "id": "o1"},
+        "address": "Munich, Germany",
+        "latitude": 48.137,
+        "longitude": 11.575,
+        "jurisdiction": "DE-BY"
+    }"#;
+    let transport = MockTransport::new()
+        .with_response("plants", HttpResponse::ok(&format!("[{plant}]")))
+        .with_response("plants/p1", HttpResponse::ok(plant))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport);
+
+    let Ec3Result::Plants(plants) = api.endpoint(Endpoint::Plants).fetch_all()? else {
+        panic!("Expected plants");
+    };
+    assert_eq!(plants[0].owned_by.as_ref().unwrap().name, "Acme");
+    assert_eq!(plants[0].jurisdiction, Some("DE-BY".parse()?));
+    let abroad: crate::models::Plant =
+        serde_json::from_str(r#"{"id": "p2", "name": "Paris plant", "jurisdiction": "FR-75"}"#)?;
+    assert_eq!(abroad.jurisdiction, Some(Jurisdiction::country("FR")?));
+
+    let mut material: crate::models::Ec3Material =
+        serde_json::from_str(&material_json("a")).unwrap();
+    assert!(api.plant_of(&material)?.is_none());
+    material.plant_or_group = Some(plants[0].clone());
+    let plant = api.plant_of(&material)?.expect("Expected a plant");
+
+    // Munich to Berlin is roughly 504 km
+    let distance = plant.distance_km(52.520, 13.405).unwrap();
+    assert!((distance - 504.).abs() < 5., "{distance}");
+    Ok(())
+}
+
+#[test]
+fn unknown_plant_jurisdiction_ignored() -> Result<()> {
+    let mut kosovo: serde_json::Value = serde_json::from_str(&material_json("k"))?;
+    kosovo["plant_or_group"] =
+        serde_json::json!({"id": "p9", "name": "Pristina plant", "jurisdiction": "XK"});
+    let transport = MockTransport::new()
+        .with_response(
+            "materials",
+            HttpResponse::ok(&format!("[{kosovo}, {}]", material_json("a"))),
+        )
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport).use_cache(false);
+
+    let materials = api.fetch()?;
+
+    assert_eq!(materials.len(), 2, "The page should not fail");
+    let plant = materials[0]
+        .plant_or_group
+        .as_ref()
+        .expect("Expected a plant");
+    assert_eq!(plant.name, "Pristina plant");
+    assert!(plant.jurisdiction.is_none());
+    Ok(())
+}
+
+#[test]
+fn manufacturer_directory_lookups() -> Result<()> {
+    let acme = r#"{"id": "o1", "name": "Acme", "country": "DE", "website": "https://acme.de", "epds_count": 12}"#;
+    let other = r#"{"id": "o2", "name": "Beton AG", "country": "DE"}"#;
+    let transport = MockTransport::new()
+        .with_response(
+            "orgs",
+            HttpResponse::ok(&format!("[{acme}, {other}]")).with_header("X-Total-Pages", "2"),
+        )
+        .with_response(
+            "orgs",
+            HttpResponse::ok(&format!("[{acme}]")).with_header("X-Total-Pages", "2"),
+        )
+        .shared();
+
+    let directory = Ec3api::new("key")
+        .transport(transport)
+        .manufacturer_directory()?;
+
+    assert_eq!(directory.len(), 2, "Duplicate org should be dropped");
+    assert_eq!(directory.get("o1").unwrap().epds_count, Some(12));
+    assert_eq!(directory.find_by_name("beton")[0].id, "o2");
+
+    let material: crate::models::Ec3Material = serde_json::from_str(&material_json("a")).unwrap();
+    let org = directory
+        .by_manufacturer(&material.manufacturer)
+        .expect("Expected manufacturer's org");
+    assert_eq!(org.website.as_deref(), Some("https://acme.de"));
+    Ok(())
+}
+
+fn temp_cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ec3api-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn category_with_partial_subcategories() -> Result<()> {
+    let category = r#"{
+        "id": "c1",
+        "name": "Concrete",
+        "declared_unit": "1 m3",
+        "subcategories": [
+            {"id": "c2", "name": "ReadyMix", "declared_unit": null},
+            {"id": "c3", "declared_unit": "1 m3"}
+        ]
+    }"#;
+    let transport = MockTransport::new()
+        .with_response("categories/c1", HttpResponse::ok(category))
+        .with_response(
+            "categories/c4",
+            HttpResponse::ok(
+                r#"{"id": "c4", "name": "Steel", "subcategories": [{"id": "c5", "name": "Rebar"}]}"#,
+            ),
+        )
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport).use_cache(false);
+
+    assert!(
+        api.category_by_id("c1").is_err(),
+        "A subcategory without a name should be an error"
+    );
+    let steel = api.category_by_id("c4")?;
+    let children = steel.children.unwrap();
+    assert_eq!(children[0].value.name, "Rebar");
+    assert!(children[0].children.as_ref().unwrap().is_empty());
+    Ok(())
+}
+
+#[test]
+fn fetch_by_id_uses_cache() -> Result<()> {
+    let category = r#"{
+        "id": "c1",
+        "name": "Concrete",
+        "declared_unit": "1 m3",
+        "subcategories": [
+            {"id": "c2", "name": "ReadyMix", "declared_unit": "1 m3", "subcategories": []}
+        ]
+    }"#;
+    let transport = MockTransport::new()
+        .with_response("materials/a", HttpResponse::ok(&material_json("a")))
+        .with_response("categories/c1", HttpResponse::ok(category))
+        .shared();
+    let cache_dir = temp_cache_dir("by-id");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone());
+
+    for _ in 0..2 {
+        let material = api.material_by_id("a")?;
+        assert_eq!(material.name, "Material a");
+        assert_eq!(material.gwp.value, 250.5);
+
+        let category = api.category_by_id("c1")?;
+        assert_eq!(category.value.declared_unit.unit, crate::models::Unit::M3);
+        assert_eq!(category.children.unwrap()[0].value.name, "ReadyMix");
+    }
+
+    assert_eq!(
+        transport.requests().len(),
+        2,
+        "Second round should be cached"
+    );
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn category_stats_position() -> Result<()> {
+    let stats = r#"{
+        "id": "c1",
+        "name": "Concrete",
+        "declared_unit": "1 m3",
+        "epds_count": 5000,
+        "pct10_gwp": "200 kgCO2e",
+        "pct20_gwp": "240 kgCO2e",
+        "pct50_gwp": "300 kgCO2e",
+        "pct90_gwp": "400 kgCO2e",
+        "pct30_gwp": null,
+        "conservative_estimate": "380 kgCO2e"
+    }"#;
+    let transport = MockTransport::new()
+        .with_response("categories/c1", HttpResponse::ok(stats))
+        .shared();
+
+    let stats = Ec3api::new("key")
+        .transport(transport)
+        .category_stats("c1")?;
+    let material: crate::models::Ec3Material = serde_json::from_str(&material_json("a")).unwrap();
+
+    assert_eq!(stats.percentiles().len(), 4);
+    let percentile = stats.percentile_of(&material).unwrap();
+    assert!((percentile - 25.25).abs() < 1e-9, "{percentile}");
+    assert_eq!(stats.is_below_baseline(&material), Some(true));
+    assert_eq!(stats.is_achievable(&material), Some(false));
+    Ok(())
+}
+
+#[test]
+fn project_bill_of_materials() -> Result<()> {
+    let buildings =
+        r#"[{"id": "b1", "name": "Tower", "project": "p1", "gross_floor_area": 1200.0}]"#;
+    let quantities = format!(
+        r#"[{{"material": {}, "quantity": "10 m3"}}, {{"material": {}, "quantity": "5 kg"}}]"#,
+        material_json("a"),
+        material_json("b")
+    );
+    let transport = MockTransport::new()
+        .with_response("projects/p1/buildings", HttpResponse::ok(buildings))
+        .with_response("buildings/b1/materials", HttpResponse::ok(&quantities))
+        .shared();
+
+    let bills = Ec3api::new("key")
+        .transport(transport)
+        .project_bills_of_materials("p1")?;
+
+    assert_eq!(bills.len(), 1);
+    let bill = &bills[0];
+    assert_eq!(bill.building.name, "Tower");
+    assert_eq!(bill.items[1].gwp(), None, "kg cannot be converted to m3");
+    assert_eq!(bill.total_gwp(), 2505.);
+    let by_category = bill.gwp_by_category();
+    assert_eq!(by_category.values().sum::<f64>(), 2505.);
+    Ok(())
+}
+
+#[test]
+fn jurisdiction_parsing_and_containment() -> Result<()> {
+    let europe: Jurisdiction = "150".parse()?;
+    let bavaria: Jurisdiction = "de-by".parse()?;
+    let germany: Jurisdiction = "Germany".parse()?;
+
+    assert_eq!(europe, Jurisdiction::region(150)?);
+    assert_eq!(europe.name(), Some("Europe"));
+    assert_eq!(bavaria.parent(), Some(germany));
+    assert!(europe.contains(&bavaria));
+    assert!(Jurisdiction::WORLD.contains(&bavaria));
+    assert!(!europe.contains(&"US-CA".parse()?));
+    assert!("Western Europe".parse::<Jurisdiction>()?.contains(&germany));
+    assert_eq!("UK".parse::<Jurisdiction>()?, Jurisdiction::country("GB")?);
+    assert_eq!(Jurisdiction::region(5)?.to_string(), "005");
+    assert!("XX".parse::<Jurisdiction>().is_err());
+    assert!(Jurisdiction::country("XX").is_err());
+    assert_eq!(Jurisdiction::country("de")?, germany);
+    assert_eq!(bavaria.kind(), JurisdictionKind::Subdivision);
+    assert_eq!(europe.countries().len(), 51);
+
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+    Ec3api::new("key")
+        .transport(transport.clone())
+        .jurisdiction(europe)
+        .fetch()?;
+    assert_eq!(
+        transport.requests()[0].query_param("jurisdiction"),
+        Some("150")
+    );
+    Ok(())
+}
+
+#[test]
+fn fetch_merges_jurisdictions() -> Result<()> {
+    let germany: Jurisdiction = "DE".parse()?;
+    let france: Jurisdiction = "FR".parse()?;
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 1))
+        .with_response("materials", materials_page(&["b", "c"], 1))
+        .shared();
+
+    let tagged = Ec3api::new("key")
+        .transport(transport.clone())
+        .jurisdictions(vec![germany, france])
+        .fetch_by_jurisdiction()?;
+
+    let tags: Vec<(&str, String)> = tagged
+        .iter()
+        .map(|t| (t.material.id.as_str(), t.jurisdiction.code()))
+        .collect();
+    assert_eq!(
+        tags,
+        vec![("a", "DE".into()), ("b", "DE".into()), ("c", "FR".into())]
+    );
+    let requests = transport.requests();
+    assert_eq!(requests[0].query_param("jurisdiction"), Some("DE"));
+    assert_eq!(requests[1].query_param("jurisdiction"), Some("FR"));
+    Ok(())
+}
+
+#[test]
+fn iter_materials_walks_jurisdictions() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 1))
+        .with_response("materials", materials_page(&["b", "c"], 1))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport).jurisdictions(vec![
+        Jurisdiction::country("DE").unwrap(),
+        Jurisdiction::country("fr").unwrap(),
+    ]);
+
+    let ids: Vec<String> = api.iter_materials().map(|m| m.unwrap().id).collect();
+
+    assert_eq!(ids, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn typed_queries() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 2))
+        .with_response("materials", materials_page(&["c"], 2))
+        .with_response("categories/abc", HttpResponse::ok(r#"{"id": "abc", "name": "Concrete", "declared_unit": "1 m3", "subcategories": []}"#))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone()).all_pages(true);
+
+    let materials: Vec<Ec3Material> = api.execute(&MaterialsQuery {
+        filter: Some(MaterialFilter::of_category("Concrete")),
+        jurisdiction: Some(Jurisdiction::country("FR")?),
+    })?;
+    let category: Node<Ec3Category> = api.execute(&CategoryQuery { id: "abc".into() })?;
+
+    assert_eq!(materials.len(), 3);
+    assert_eq!(category.value.name, "Concrete");
+    let requests = transport.requests();
+    assert_eq!(requests[0].query_param("jurisdiction"), Some("FR"));
+    Ok(())
+}
+
+#[test]
+fn unmodelled_fields_are_kept() -> Result<()> {
+    let material = material_json("a").replace(
+        r#""id": "a","#,
+        r#""id": "a", "concrete_compressive_strength_28d": "30 MPa","#,
+    );
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::ok(&format!("[{material}]")))
+        .shared();
+    let cache_dir = temp_cache_dir("extra");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"));
+
+    for _ in 0..2 {
+        let materials = api.fetch()?;
+        assert_eq!(
+            materials[0].extra["concrete_compressive_strength_28d"],
+            "30 MPa"
+        );
+    }
+    assert_eq!(
+        transport.requests().len(),
+        1,
+        "Second fetch should be cached"
+    );
+
+    #[derive(serde::Deserialize)]
+    struct Strength {
+        id: String,
+        concrete_compressive_strength_28d: String,
+    }
+    let raw = api.fetch_raw()?;
+    assert_eq!(raw[0]["id"], "a");
+    let strengths: Vec<Strength> = api.fetch_as()?;
+    assert_eq!(strengths[0].id, "a");
+    assert_eq!(strengths[0].concrete_compressive_strength_28d, "30 MPa");
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn client_shared_between_threads() -> Result<()> {
+    fn assert_send_sync<T: Clone + Send + Sync>() {}
+    assert_send_sync::<Ec3api>();
+
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 1))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone()).use_cache(false);
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            let api = api.clone();
+            s.spawn(move || assert_eq!(api.fetch().unwrap().len(), 2));
+        }
+    });
+    let first = api.with(|req| {
+        req.max_records(1);
+    });
+
+    assert_eq!(first.fetch()?.len(), 1);
+    assert_eq!(api.fetch()?.len(), 2, "Overrides should not leak");
+    assert_eq!(transport.requests().len(), 6);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_client_fetches() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 1))
+        .with_response(
+            "epds/a",
+            HttpResponse::ok(r#"{"id": "a", "name": "Ready mix", "declared_unit": "1 m3"}"#),
+        )
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone()).use_cache(false);
+    let api = crate::async_client::AsyncEc3api::new(api);
+
+    assert_eq!(api.fetch().await?.len(), 2);
+    assert_eq!(api.epd("a").await?.name, "Ready mix");
+    assert_eq!(transport.requests().len(), 2);
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn dropping_async_call_cancels_it() {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(503, ""))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .retry_policy(RetryPolicy {
+            max_attempts: u32::MAX,
+            base_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+            respect_retry_after: false,
+            ..Default::default()
+        });
+    let api = crate::async_client::AsyncEc3api::new(api);
+    let pause =
+        |ms| tokio::task::spawn_blocking(move || std::thread::sleep(Duration::from_millis(ms)));
+
+    let call = tokio::spawn(async move { api.fetch().await });
+    pause(50).await.unwrap();
+    call.abort();
+    assert!(call.await.unwrap_err().is_cancelled());
+    pause(50).await.unwrap();
+    let sent = transport.requests().len();
+    pause(50).await.unwrap();
+
+    assert!(sent > 0);
+    assert_eq!(
+        transport.requests().len(),
+        sent,
+        "Retries should stop once the future is dropped"
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn async_stale_entry_refreshed_after_call() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", HttpResponse::new(503, ""))
+        .with_response("materials", materials_page(&["b"], 1))
+        .shared();
+    let cache_dir = temp_cache_dir("async_refresh");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .retry_policy(RetryPolicy {
+            base_delay: Duration::from_millis(50),
+            jitter: false,
+            respect_retry_after: false,
+            ..Default::default()
+        })
+        .material_filter(MaterialFilter::of_category("Concrete"));
+    assert_eq!(api.fetch()?[0].id, "a");
+
+    api.cache_ttl(Endpoint::Materials, Duration::ZERO)
+        .cache_mode(CacheMode::StaleWhileRevalidate);
+    let async_api = crate::async_client::AsyncEc3api::new(api.clone());
+    assert_eq!(async_api.fetch().await?[0].id, "a");
+
+    // The refresh retries past the end of the call
+    let started = std::time::Instant::now();
+    while transport.requests().len() < 3 && started.elapsed() < Duration::from_secs(5) {
+        tokio::task::spawn_blocking(|| std::thread::sleep(Duration::from_millis(10)))
+            .await
+            .unwrap();
+    }
+    assert_eq!(transport.requests().len(), 3);
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn fetch_batch_keeps_going_after_failures() {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", HttpResponse::new(500, "oops"))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .retry_policy(RetryPolicy::none())
+        .workers(2);
+
+    let mut strong = MaterialFilter::of_category("Concrete");
+    strong.add_filter("concrete_compressive_strength_28d", ">", vec!["40 MPa"]);
+    let filters = vec![
+        MaterialFilter::of_category("Concrete"),
+        strong,
+        MaterialFilter::of_category("Wood"),
+    ];
+    let results = api.fetch_batch(filters);
+
+    assert_eq!(
+        results.len(),
+        3,
+        "Filters on the same category should be kept apart"
+    );
+    let categories: Vec<String> = results.iter().map(|(mf, _)| mf.get_category()).collect();
+    assert_eq!(categories, vec!["Concrete", "Concrete", "Wood"]);
+    assert_eq!(results.iter().filter(|(_, r)| r.is_ok()).count(), 1);
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[test]
+fn observer_receives_events() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(503, ""))
+        .with_response("materials", materials_page(&["a", "b"], 2))
+        .with_response("materials", materials_page(&["c"], 2))
+        .shared();
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = events.clone();
+    let cache_dir = temp_cache_dir("observer");
+    let mut api = Ec3api::new("key");
+    api.transport(transport)
+        .retry_policy(no_delay_retries(2))
+        .cache_dir(cache_dir.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"))
+        .all_pages(true)
+        .observer(Arc::new(move |event: &Event| {
+            let name = match event {
+                Event::RequestStarted { attempt, .. } => format!("request {attempt}"),
+                Event::PageReceived { records, .. } => format!("page {records}"),
+                Event::RetryScheduled { status, .. } => format!("retry {status:?}"),
+                Event::CacheHit { .. } => "hit".to_string(),
+                Event::CacheMiss { .. } => "miss".to_string(),
+                Event::Finished { records, .. } => format!("finished {records}"),
+            };
+            seen.lock().unwrap().push(name);
+        }));
+
+    api.fetch()?;
+    api.fetch()?;
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "miss",
+            "request 1",
+            "retry Some(503)",
+            "request 2",
+            "page 2",
+            "request 1",
+            "page 1",
+            "finished 3",
+            "hit"
+        ]
+    );
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn cancel_and_deadline() {
+    let transport = MockTransport::new()
+        .with_response("materials", HttpResponse::new(503, ""))
+        .shared();
+    let token = CancelToken::new();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .retry_policy(RetryPolicy {
+            base_delay: Duration::from_secs(60),
+            jitter: false,
+            respect_retry_after: false,
+            ..Default::default()
+        })
+        .cancel_token(token.clone());
+
+    let started = std::time::Instant::now();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        token.cancel();
+    });
+    let result = api.fetch();
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(error::ApiError::Cancelled)));
+    assert!(
+        started.elapsed() < Duration::from_secs(10),
+        "Retry delay should be cut short"
+    );
+    assert_eq!(transport.requests().len(), 1);
+
+    let expired = api.with(|req| {
+        req.cancel_token(CancelToken::new())
+            .deadline(Duration::ZERO);
+    });
+    assert!(matches!(
+        expired.fetch(),
+        Err(error::ApiError::DeadlineExceeded)
+    ));
+}
+
+#[test]
+fn deadline_counts_from_each_operation() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .use_cache(false)
+        .deadline(Duration::from_millis(100));
+
+    assert_eq!(api.fetch()?.len(), 1);
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(
+        api.fetch()?.len(),
+        1,
+        "A later fetch should get its own deadline"
+    );
+    assert_eq!(api.iter_materials().count(), 1);
+    assert_eq!(transport.requests().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn cache_keyed_by_full_query() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", materials_page(&["b"], 1))
+        .shared();
+    let cache_dir = temp_cache_dir("keys");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"));
+    let france = api.with(|req| {
+        req.jurisdiction(Jurisdiction::country("FR").unwrap());
+    });
+
+    for _ in 0..2 {
+        assert_eq!(api.fetch()?[0].id, "a");
+        assert_eq!(france.fetch()?[0].id, "b");
+    }
+    assert_eq!(transport.requests().len(), 2);
+
+    let index: serde_json::Map<String, serde_json::Value> =
+        crate::utils::read_cache_as(&cache_dir, crate::utils::CACHE_INDEX)?;
+    let mut jurisdictions: Vec<&serde_json::Value> = index
+        .values()
+        .map(|q| &q["query"]["jurisdictions"][0])
+        .collect();
+    jurisdictions.sort_by_key(|j| j.to_string());
+    assert_eq!(jurisdictions, vec!["DE", "FR"]);
+    assert!(index.keys().all(|key| key.starts_with("Concrete_")));
+    assert!(index.values().all(|q| q["fetched_at"].as_u64().is_some()));
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn cache_ttl_and_modes() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", materials_page(&["b"], 1))
+        .with_response("materials", materials_page(&["c"], 1))
+        .shared();
+    let cache_dir = temp_cache_dir("ttl");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"));
+    assert_eq!(api.fetch()?[0].id, "a");
+
+    api.cache_ttl(Endpoint::Materials, Duration::ZERO);
+    assert_eq!(
+        api.fetch()?[0].id,
+        "b",
+        "Expired entry should be fetched again"
+    );
+
+    api.cache_mode(CacheMode::StaleWhileRevalidate);
+    assert_eq!(api.fetch()?[0].id, "b", "Stale entry should be served");
+    let started = std::time::Instant::now();
+    while transport.requests().len() < 3 && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        transport.requests().len(),
+        3,
+        "Stale entry should be refreshed"
+    );
+
+    api.cache_mode(CacheMode::OnlyIfCached);
+    let mut served = api.fetch()?[0].id.clone();
+    while served != "c" && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+        served = api.fetch()?[0].id.clone();
+    }
+    assert_eq!(served, "c");
+    assert!(matches!(
+        api.material_by_id("x"),
+        Err(error::ApiError::NotCached(_))
+    ));
+    assert_eq!(transport.requests().len(), 3);
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn cache_age_read_from_index() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials/x", HttpResponse::ok(&material_json("x")))
+        .shared();
+    let cache_dir = temp_cache_dir("fetched_at");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .cache_ttl(Endpoint::Materials, Duration::from_secs(3600));
+    api.material_by_id("x")?;
+    api.material_by_id("x")?;
+    assert_eq!(transport.requests().len(), 1);
+
+    // Entries are as old as their recorded fetch, however recently their file was written
+    let mut index: serde_json::Map<String, serde_json::Value> =
+        crate::utils::read_cache_as(&cache_dir, crate::utils::CACHE_INDEX)?;
+    index["material_x"]["fetched_at"] = 0.into();
+    std::fs::write(
+        cache_dir.join(format!("{}.json", crate::utils::CACHE_INDEX)),
+        serde_json::to_string(&index)?,
+    )?;
+    api.material_by_id("x")?;
+    assert_eq!(
+        transport.requests().len(),
+        2,
+        "Expired entry should be fetched again"
+    );
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn stale_entry_refreshed_once() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", materials_page(&["b"], 1))
+        .shared();
+    let cache_dir = temp_cache_dir("refresh_once");
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .cache_dir(cache_dir.clone())
+        .rate_limiter(Arc::new(RateLimiter::new(5., 1)))
+        .material_filter(MaterialFilter::of_category("Concrete"));
+    assert_eq!(api.fetch()?[0].id, "a");
+
+    // The limiter holds the refresh back while the entry is read again
+    api.cache_ttl(Endpoint::Materials, Duration::ZERO)
+        .cache_mode(CacheMode::StaleWhileRevalidate);
+    for _ in 0..5 {
+        assert_eq!(api.fetch()?[0].id, "a");
+    }
+    std::thread::sleep(Duration::from_millis(500));
+
+    assert_eq!(transport.requests().len(), 2, "Only one refresh should run");
+    assert_eq!(api.fetch()?[0].id, "b");
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+/// Panics on the second request, as a refresh failing to create the cache dir would
+struct PanicOnSecondRequest(Arc<MockTransport>);
+
+impl crate::transport::Transport for PanicOnSecondRequest {
+    fn send(&self, request: &crate::transport::HttpRequest) -> Result<HttpResponse> {
+        let response = self.0.send(request);
+        assert_ne!(self.0.requests().len(), 2, "refresh panicked");
+        response
+    }
+}
+
+#[test]
+fn panicking_refresh_can_be_retried() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a"], 1))
+        .with_response("materials", materials_page(&["b"], 1))
+        .shared();
+    let cache_dir = temp_cache_dir("refresh_panic");
+    let mut api = Ec3api::new("key");
+    api.transport(Arc::new(PanicOnSecondRequest(transport.clone())))
+        .cache_dir(cache_dir.clone())
+        .material_filter(MaterialFilter::of_category("Concrete"));
+    assert_eq!(api.fetch()?[0].id, "a");
+
+    api.cache_ttl(Endpoint::Materials, Duration::ZERO)
+        .cache_mode(CacheMode::StaleWhileRevalidate);
+    assert_eq!(api.fetch()?[0].id, "a");
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(transport.requests().len(), 2);
+
+    assert_eq!(api.fetch()?[0].id, "a");
+    let started = std::time::Instant::now();
+    while transport.requests().len() < 3 && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        transport.requests().len(),
+        3,
+        "Entry should be refreshed after the panic"
+    );
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+    Ok(())
+}
+
+#[test]
+fn cache_index_written_aside() {
+    let cache_dir = temp_cache_dir("index_aside");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    crate::utils::write_cache_index(&cache_dir, "k0", serde_json::json!({}));
+
+    std::thread::scope(|s| {
+        for writer in 1..5 {
+            let cache_dir = &cache_dir;
+            s.spawn(move || {
+                for i in 0..20 {
+                    let key = format!("k{writer}_{i}");
+                    crate::utils::write_cache_index(cache_dir, &key, serde_json::json!({}));
+                }
+            });
+        }
+        for _ in 0..200 {
+            assert!(
+                crate::utils::cache_age(&cache_dir, "k0").is_some(),
+                "The index should never be read half written"
+            );
+        }
+    });
+
+    let index: serde_json::Map<String, serde_json::Value> =
+        crate::utils::read_cache_as(&cache_dir, crate::utils::CACHE_INDEX).unwrap();
+    assert_eq!(index.len(), 81);
+    let _ = std::fs::remove_dir_all(cache_dir);
+}
+
+#[test]
+fn listings_ignore_material_paging() -> Result<()> {
+    let org = r#"{"id": "o1", "name": "Acme"}"#;
+    let transport = MockTransport::new()
+        .with_response(
+            "orgs",
+            HttpResponse::ok(&format!("[{org}]")).with_header("X-Total-Pages", "2"),
+        )
+        .with_response(
+            "orgs",
+            HttpResponse::ok(r#"[{"id": "o2", "name": "Beton AG"}]"#)
+                .with_header("X-Total-Pages", "2"),
+        )
+        .shared();
+
+    let directory = Ec3api::new("key")
+        .transport(transport.clone())
+        .page_number(3)
+        .max_records(0)
+        .manufacturer_directory()?;
+
+    assert_eq!(directory.len(), 2);
+    let requests = transport.requests();
+    assert_eq!(requests[0].query_param("page_number"), Some("1"));
+    assert_eq!(requests[1].query_param("page_number"), Some("2"));
+    Ok(())
+}
+
+#[test]
+fn fetch_all_walks_listings() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response(
+            "projects",
+            HttpResponse::ok(r#"[{"id": "p1", "name": "Tower"}]"#)
+                .with_header("X-Total-Pages", "2"),
+        )
+        .with_response(
+            "projects",
+            HttpResponse::ok(r#"[{"id": "p2", "name": "School"}]"#)
+                .with_header("X-Total-Pages", "2"),
+        )
+        .shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .endpoint(Endpoint::Projects)
+        .material_filter(MaterialFilter::of_category("Concrete"));
+
+    let Ec3Result::Projects(projects) = api.fetch_all()? else {
+        panic!("Expected projects");
+    };
+
+    assert_eq!(projects.len(), 2);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests
+        .iter()
+        .all(|r| r.query_param("jurisdiction").is_none() && r.query_param("mf").is_none()));
+    Ok(())
+}
+
+#[test]
+fn bill_of_materials_walks_pages() -> Result<()> {
+    let building = r#"{"id": "b1", "name": "Tower", "project": "p1"}"#;
+    let quantity = |id: &str| {
+        format!(
+            r#"[{{"material": {}, "quantity": "10 m3"}}]"#,
+            material_json(id)
+        )
+    };
+    let transport = MockTransport::new()
+        .with_response(
+            "projects/p1/buildings",
+            HttpResponse::ok(&format!("[{building}]")).with_header("X-Total-Pages", "1"),
+        )
+        .with_response(
+            "buildings/b1/materials",
+            HttpResponse::ok(&quantity("a")).with_header("X-Total-Pages", "2"),
+        )
+        .with_response(
+            "buildings/b1/materials",
+            HttpResponse::ok(&quantity("b")).with_header("X-Total-Pages", "2"),
+        )
+        .shared();
+
+    let bills = Ec3api::new("key")
+        .transport(transport.clone())
+        .project_bills_of_materials("p1")?;
+
+    assert_eq!(bills[0].items.len(), 2);
+    assert_eq!(bills[0].total_gwp(), 5010.);
+    assert_eq!(transport.requests().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn fetch_all_pages_materials() -> Result<()> {
+    let transport = MockTransport::new()
+        .with_response("materials", materials_page(&["a", "b"], 2))
+        .with_response("materials", materials_page(&["c"], 2))
+        .shared();
+
+    let result = Ec3api::new("key")
+        .transport(transport.clone())
+        .use_cache(false)
+        .page_size(2)
+        .all_pages(true)
+        .fetch_all()?;
+
+    let Ec3Result::Materials(materials) = result else {
+        panic!("Expected materials");
+    };
+    assert_eq!(materials.len(), 3);
+    assert_eq!(transport.requests()[0].query_param("page_size"), Some("2"));
+    Ok(())
+}
+
+#[test]
+fn fetch_rejects_other_endpoints() {
+    let transport = MockTransport::new().shared();
+    let mut api = Ec3api::new("key");
+    api.transport(transport.clone())
+        .endpoint(Endpoint::Categories);
+
+    assert!(matches!(
+        api.fetch(),
+        Err(error::ApiError::NotMaterialsEndpoint(_))
+    ));
+    assert!(transport.requests().is_empty());
+}
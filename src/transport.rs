@@ -0,0 +1,263 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use crate::error::ApiError;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Http method of a [`HttpRequest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// A request as issued by [`crate::Ec3api`] to its [`Transport`]
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub query: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Time allowed for the whole request, if limited
+    pub timeout: Option<Duration>,
+}
+
+/// A response of any status code returned by a [`Transport`]
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Sends the requests of [`crate::Ec3api`].
+///
+/// Implementations return `Ok` for every response received, whatever its status,
+/// and `Err` only when no response could be obtained.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, ApiError>;
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        HttpRequest {
+            method: Method::Get,
+            url: url.to_string(),
+            headers: Vec::new(),
+            query: Vec::new(),
+            body: None,
+            timeout: None,
+        }
+    }
+    pub fn post(url: &str, body: &str) -> Self {
+        HttpRequest {
+            method: Method::Post,
+            url: url.to_string(),
+            headers: Vec::new(),
+            query: Vec::new(),
+            body: Some(body.to_string()),
+            timeout: None,
+        }
+    }
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+    pub fn query(mut self, param: &str, value: &str) -> Self {
+        self.query.push((param.to_string(), value.to_string()));
+        self
+    }
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    /// Value of the header `name`, compared case-insensitively
+    pub fn header_value(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+    /// Value of the first query parameter named `param`
+    pub fn query_param(&self, param: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(p, _)| p == param)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+    /// A 200 response with the given body
+    pub fn ok(body: &str) -> Self {
+        Self::new(200, body)
+    }
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+    /// Value of the header `name`, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Default [`Transport`] sending requests over the network with ureq.
+///
+/// Connections are pooled by the agent and reused by every request sent through it.
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+impl UreqTransport {
+    /// Transport with the default timeouts, 30 seconds to connect and 60 seconds per read
+    pub fn new() -> Self {
+        Self::with_timeouts(DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT)
+    }
+    /// Transport giving up on connections and reads taking longer than the given timeouts
+    pub fn with_timeouts(connect: Duration, read: Duration) -> Self {
+        UreqTransport {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(connect)
+                .timeout_read(read)
+                .build(),
+        }
+    }
+    /// Send requests with a configured agent, e.g. to size its connection pool
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        UreqTransport { agent }
+    }
+    /// Transport shared by every client created without one, so they share a connection pool
+    pub(crate) fn shared() -> Arc<dyn Transport> {
+        static SHARED: OnceLock<Arc<UreqTransport>> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Arc::new(UreqTransport::new()))
+            .clone()
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, ApiError> {
+        let method = match request.method {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        };
+        let mut req = self.agent.request(method, &request.url);
+        for (name, value) in &request.headers {
+            req = req.set(name, value);
+        }
+        for (param, value) in &request.query {
+            req = req.query(param, value);
+        }
+        if let Some(timeout) = request.timeout {
+            req = req.timeout(timeout);
+        }
+        let result = match &request.body {
+            Some(body) => req.send_string(body),
+            None => req.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(e)) => return Err(ApiError::TransportError(e.to_string())),
+        };
+
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let body = response
+            .into_string()
+            .map_err(|e| ApiError::TransportError(e.to_string()))?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// In-memory [`Transport`] serving canned responses, for running without network access.
+///
+/// Responses are routed by the path the request url ends with. Responses registered for the
+/// same path are served in order, the last one being repeated. Unrouted requests get a 404.
+#[derive(Default)]
+pub struct MockTransport {
+    routes: Mutex<Vec<(String, VecDeque<HttpResponse>)>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Queue `response` for requests whose url ends with `path`
+    pub fn with_response(self, path: &str, response: HttpResponse) -> Self {
+        {
+            let mut routes = self.routes.lock().unwrap();
+            match routes.iter_mut().find(|(p, _)| p == path) {
+                Some((_, responses)) => responses.push_back(response),
+                None => routes.push((path.to_string(), VecDeque::from([response]))),
+            }
+        }
+        self
+    }
+    /// Wrap into an `Arc` to hand to [`crate::Ec3api::transport`] while keeping access to it
+    pub fn shared(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, ApiError> {
+        self.requests.lock().unwrap().push(request.clone());
+
+        let mut routes = self.routes.lock().unwrap();
+        let route = routes
+            .iter_mut()
+            .filter(|(path, _)| request.url.ends_with(path.as_str()))
+            .max_by_key(|(path, _)| path.len());
+
+        let response = match route {
+            Some((_, responses)) if responses.len() > 1 => responses.pop_front(),
+            Some((_, responses)) => responses.front().cloned(),
+            None => None,
+        };
+        Ok(response.unwrap_or_else(|| HttpResponse::new(404, "")))
+    }
+}
@@ -1,8 +1,14 @@
 use std::{
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
 use crate::{
     error::ApiError,
     models::{Category, DeclaredUnit, Ec3Material, Gwp, GwpUnits, Manufacturer, Unit},
@@ -10,21 +16,120 @@ use crate::{
 
 pub fn write_cache(path: &PathBuf, json: String, filename: &str) {
     if !path.exists() {
-        println!("No cache folder, creating");
+        debug!(path:? = path; "no cache folder, creating");
         std::fs::create_dir(path).expect("Unable to create cache dir");
     }
 
-    let output = path.join(format!("{}.json", filename));
-
-    match std::fs::write(output, json) {
+    match write_aside(path, filename, json) {
         Ok(_) => {
-            println!("Results cached")
+            debug!(cache_key = filename; "results cached")
         }
         Err(e) => {
-            println!("Could not write JSON file: {e:?}")
+            warn!(cache_key = filename; "could not write JSON file: {e}")
         }
     };
 }
+/// Write the cache file `filename` aside and rename it, so that files refreshed in the
+/// background are never read half written
+fn write_aside(path: &Path, filename: &str, json: String) -> std::io::Result<()> {
+    let output = path.join(format!("{}.json", filename));
+    let partial = path.join(format!(
+        "{}.json.{}.{:?}.tmp",
+        filename,
+        std::process::id(),
+        std::thread::current().id()
+    ));
+    std::fs::write(&partial, json).and_then(|_| std::fs::rename(&partial, output))
+}
+
+/// How cache entries older than the ttl of their endpoint are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Fetch again, replacing the entry
+    #[default]
+    Fresh,
+    /// Serve the entry and refresh it in the background
+    StaleWhileRevalidate,
+    /// Serve the entry. Nothing is fetched from the api, missing entries are
+    /// [`ApiError::NotCached`] errors
+    OnlyIfCached,
+}
+
+/// Time since the results of the cache entry `cache_key` were fetched, if recorded in the
+/// cache index
+pub fn cache_age(path: &Path, cache_key: &str) -> Option<Duration> {
+    let index: Map<String, Value> = read_cache_as(path, CACHE_INDEX).ok()?;
+    let fetched_at = index.get(cache_key)?.get("fetched_at")?.as_u64()?;
+    Some(unix_millis().saturating_sub(Duration::from_millis(fetched_at)))
+}
+
+/// Time since the unix epoch
+fn unix_millis() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Name of the file mapping cache keys to the queries they hold the results of, and when these
+/// were fetched
+pub const CACHE_INDEX: &str = "index";
+
+/// Serializes updates of the cache index between threads
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// 64 bit FNV-1a hash, stable across runs and platforms unlike the std hashers
+pub fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Record in the cache index which query the entry `cache_key` holds, fetched just now
+pub fn write_cache_index(path: &Path, cache_key: &str, query: Value) {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut index: Map<String, Value> = match read_cache_as(path, CACHE_INDEX) {
+        Ok(index) => index,
+        Err(ApiError::CacheError(e)) if e.kind() == std::io::ErrorKind::NotFound => Map::new(),
+        Err(e) => {
+            warn!(cache_key; "replacing unreadable cache index: {e}");
+            Map::new()
+        }
+    };
+    let fetched_at = u64::try_from(unix_millis().as_millis()).unwrap_or(u64::MAX);
+    index.insert(
+        cache_key.to_string(),
+        serde_json::json!({ "query": query, "fetched_at": fetched_at }),
+    );
+
+    match serde_json::to_string_pretty(&index).map(|json| write_aside(path, CACHE_INDEX, json)) {
+        Ok(Ok(_)) => debug!(cache_key; "cache index updated"),
+        Ok(Err(e)) => warn!(cache_key; "could not write cache index: {e}"),
+        Err(e) => warn!(cache_key; "could not write cache index: {e}"),
+    }
+}
+
+/// Read a cache entry written from any serializable value
+pub fn read_cache_as<T: DeserializeOwned>(path: &Path, filename: &str) -> Result<T, ApiError> {
+    let output = path.join(format!("{}.json", filename));
+
+    let contents = std::fs::read_to_string(output)?;
+
+    Ok(serde_json::from_str(&contents)?)
+}
+/// Keys of a cached material read into its typed fields, every other key goes to `extra`
+const MATERIAL_FIELDS: [&str; 10] = [
+    "name",
+    "gwp",
+    "image",
+    "manufacturer",
+    "description",
+    "id",
+    "category",
+    "declared_unit",
+    "open_xpd_uuid",
+    "plant_or_group",
+];
+
 pub fn read_cache(path: &Path, category: &str) -> Result<Vec<Ec3Material>, crate::error::ApiError> {
     let output = path.join(format!("{}.json", category).as_str());
 
@@ -81,6 +186,18 @@ pub fn read_cache(path: &Path, category: &str) -> Result<Vec<Ec3Material>, crate
                     unit: Unit::from_str(m["declared_unit"]["unit"].as_str().unwrap_or(""))
                         .unwrap_or(Unit::Unknown),
                 },
+                open_xpd_uuid: m["open_xpd_uuid"].as_str().map(str::to_string),
+                plant_or_group: serde_json::from_value(m["plant_or_group"].clone()).ok(),
+                extra: m
+                    .as_object()
+                    .map(|fields| {
+                        fields
+                            .iter()
+                            .filter(|(key, _)| !MATERIAL_FIELDS.contains(&key.as_str()))
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             };
 
             out.push(material);